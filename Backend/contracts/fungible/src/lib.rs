@@ -2,7 +2,8 @@
 #![no_std]
 use core::convert::TryFrom;
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, token, Address, Env, Vec, symbol_short,
+    contract, contractclient, contracterror, contractimpl, contracttype, token, Address, BytesN, Env, Map,
+    String, Symbol, TryFromVal, Val, Vec, symbol_short,
 };
 
 #[contracterror]
@@ -22,26 +23,253 @@ pub enum Error {
     InsufficientContractBalance = 11,
     InvalidWagePeriod = 12,
     Overflow = 13,
+    RoleNotGranted = 14,
+    NoPendingEmployer = 15,
+    AlreadyMigrated = 16,
+    InvalidEmployeeRecord = 17,
+    SurplusWouldUnderfundWages = 18,
+    NotInsolvent = 19,
+    InsolvencyGracePeriod = 20,
+    OutstandingWageDebt = 21,
+    PayrollWoundDown = 22,
+    EmployerStillActive = 23,
+    PayrollClosed = 24,
+    Paused = 25,
+    InvalidLeaveWindow = 26,
+    InsufficientLeaveBalance = 27,
+    LeaveNotFound = 28,
+    InvalidEmploymentTerm = 29,
+    EmploymentEnded = 30,
+    InvalidRateChange = 31,
+    RateChangeNotFound = 32,
+    NoPendingWageChange = 33,
+    WageProposalExpired = 34,
+    OfferNotFound = 35,
+    OfferExpired = 36,
+    NotResigned = 37,
+    AlreadyResigned = 38,
+    TokenNotAllowed = 39,
+    TokenInUse = 40,
+    UnsettledBalance = 41,
+    OracleNotConfigured = 42,
+    OraclePriceUnavailable = 43,
+    OraclePriceStale = 44,
+    OraclePriceDeviation = 45,
+    NoPendingTokenMigration = 46,
+    TokenMigrationTimelocked = 47,
+    FundingRestrictionTooLarge = 48,
+    TooManyRestrictedFunders = 49,
 }
 
 const HOUR_SECONDS: u64 = 3_600;
 const DAY_SECONDS: u64 = 86_400;
 const WEEK_SECONDS: u64 = 604_800;
 const MONTH_SECONDS: u64 = 2_592_000;
+const YEAR_SECONDS: u64 = 31_536_000;
 
-const WAGE_PERIOD_HOUR: u32 = 0;
-const WAGE_PERIOD_DAY: u32 = 1;
-const WAGE_PERIOD_WEEK: u32 = 2;
-const WAGE_PERIOD_MONTH: u32 = 3;
+// Bulan kalender terpendek, dipakai sebagai batas atas rate di `PayrollTotals`
+const MIN_CALENDAR_MONTH_SECONDS: u64 = 28 * DAY_SECONDS;
+
+// Naikkan setiap kali layout storage berubah, lalu tangani di `migrate`/`migrate_employee_record`.
+// Deployment lama tidak punya `DataKey::SchemaVersion` dan dianggap versi 1.
+// v2: tambah `DataKey::Totals`
+// v3: tambah `Employee.suspended_at` dan `Employee.total_suspended_seconds`
+// v4: tambah `Employee.leave_windows`
+// v5: `Employee.wage_period` dari u32 menjadi `WagePeriod`
+// v6: tambah `Employee.accrual_remainder`
+// v7: tambah `Employee.start_at`, `Employee.end_at` dan `Employee.ended`
+// v8: tambah `Employee.scheduled_rates`
+// v9: tambah `Employee.token`; agregat, utang gaji dan shortfall dipisah per token
+// v10: tambah `Employee.wage_currency`
+const SCHEMA_VERSION: u32 = 10;
+
+// Skala fixed-point untuk rate per detik di `PayrollTotals`
+const RATE_SCALE: i128 = 1_000_000_000;
+
+// Setelah shortfall tercatat selama ini, siapa pun boleh memicu distribusi pro-rata
+const INSOLVENCY_GRACE_SECONDS: u64 = 3 * DAY_SECONDS;
+
+// Setelah wind-down, employee punya waktu selama ini untuk klaim; sesudahnya employer boleh
+// menarik seluruh sisa saldo
+const WIND_DOWN_CLAIM_PERIOD_SECONDS: u64 = 180 * DAY_SECONDS;
+
+// Batas jadwal cuti yang disimpan di satu record Employee
+const MAX_LEAVE_WINDOWS: u32 = 16;
+
+// Batas perubahan rate terjadwal yang belum berlaku per employee
+const MAX_SCHEDULED_RATES: u32 = 16;
+
+// Setiap pembayaran gaji memeriksa semua funder terbatas di token-nya, jadi jumlahnya dibatasi
+const MAX_RESTRICTED_FUNDERS: u32 = 8;
+const MAX_FUNDING_RESTRICTION_ENTRIES: u32 = 16;
+
+// Usulan penurunan gaji / ganti periode kedaluwarsa kalau tidak diterima dalam waktu ini
+const WAGE_PROPOSAL_TTL_SECONDS: u64 = 14 * DAY_SECONDS;
+
+// Tawaran kerja yang tidak diterima dalam waktu ini hangus
+const OFFER_TTL_SECONDS: u64 = 30 * DAY_SECONDS;
+
+// Jeda antara pengumuman dan eksekusi migrasi token utama, supaya employee sempat bereaksi
+const TOKEN_MIGRATION_DELAY_SECONDS: u64 = 7 * DAY_SECONDS;
+
+// Masa notice resign kalau belum diatur lewat `set_notice_period`
+const DEFAULT_NOTICE_PERIOD_SECONDS: u64 = 2 * WEEK_SECONDS;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Employee {
     pub wage_rate: i128,
     pub last_accrual_timestamp: u64,
-    pub wage_period: u32,
+    pub wage_period: WagePeriod,
     pub accrued_balance: i128,
+    // Sisa pecahan accrual yang belum jadi satu unit token, dalam satuan 1/panjang periode
+    // segmen tempat `last_accrual_timestamp` berada; dibawa ke checkpoint berikutnya
+    pub accrual_remainder: i128,
     pub active: bool,
+    // 0 kalau tidak sedang di-freeze
+    pub suspended_at: u64,
+    // Akumulasi waktu freeze yang tidak di-accrue, untuk audit
+    pub total_suspended_seconds: u64,
+    // Jadwal cuti, tidak saling tumpang tindih; cuti unpaid tidak di-accrue
+    pub leave_windows: Vec<LeaveWindow>,
+    // Accrual dimulai di sini (bisa di masa depan)
+    pub start_at: u64,
+    // Akhir kontrak fixed-term; accrual tidak pernah melewati titik ini
+    pub end_at: Option<u64>,
+    // Sudah ditandai selesai setelah `end_at`, tinggal final sweep
+    pub ended: bool,
+    // Perubahan rate ke depan, urut naik menurut `effective_at`
+    pub scheduled_rates: Vec<RateChange>,
+    // Token (dari allowlist treasury) yang dipakai untuk membayar employee ini
+    pub token: Address,
+    // Mata uang referensi (mis. USD, IDR) kalau gaji disepakati dalam fiat. `wage_rate`,
+    // `accrued_balance` dan pesangon lalu tercatat dalam mata uang ini, dengan jumlah desimal
+    // yang sama seperti `token`, dan baru dikonversi lewat oracle saat dibayar.
+    pub wage_currency: Option<Symbol>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateChange {
+    pub effective_at: u64,
+    pub wage_rate: i128,
+}
+
+// Migrasi token utama yang sudah diumumkan dan menunggu timelock
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenMigration {
+    pub from: Address,
+    pub to: Address,
+    // Unit token baru per unit token lama, diskalakan RATE_SCALE. None berarti saldo
+    // employee di token lama harus sudah lunas sebelum eksekusi.
+    pub conversion_rate: Option<i128>,
+    pub announced_at: u64,
+    pub executable_at: u64,
+}
+
+// Perubahan yang merugikan employee, menunggu persetujuan employee sendiri
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WageProposal {
+    pub wage_rate: i128,
+    pub wage_period: WagePeriod,
+    pub proposed_at: u64,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmploymentOffer {
+    pub wage_rate: i128,
+    pub wage_period: WagePeriod,
+    pub start_at: Option<u64>,
+    pub end_at: Option<u64>,
+    // Hash dokumen kontrak kerja di luar chain, kalau ada
+    pub terms_hash: Option<BytesN<32>>,
+    pub offered_at: u64,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateSegments {
+    pub past: Vec<RateChange>,
+    pub upcoming: Vec<RateChange>,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WagePeriod {
+    Hour,
+    Day,
+    Week,
+    Biweekly,
+    // Setengah bulan kalender; gaji per periode = separuh gaji bulanan
+    SemiMonthly,
+    // 30 hari tetap, sama dengan WAGE_PERIOD_MONTH lama
+    Month,
+    // Gaji bulanan dibagi rata sepanjang panjang bulan kalender yang sebenarnya (UTC)
+    CalendarMonth,
+    // 365 atau 366 hari tergantung tahun kalender
+    Year,
+    Custom(u64),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LeaveType {
+    Vacation,
+    Sick,
+    Unpaid,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LeaveWindow {
+    pub leave_type: LeaveType,
+    pub start: u64,
+    pub end: u64,
+}
+
+// Jatah cuti berbayar (dalam detik) yang didapat per tahun masa kerja
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LeavePolicy {
+    pub vacation_seconds_per_year: u64,
+    pub sick_seconds_per_year: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LeaveBalance {
+    pub vacation_seconds: u64,
+    pub sick_seconds: u64,
+    pub unpaid_seconds_taken: u64,
+    pub accrued_until: u64,
+}
+
+// Pesangon yang dibayar saat employer memutus hubungan kerja lewat freeze/remove
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SeverancePolicy {
+    None,
+    // N kali `wage_rate` per periode gaji employee
+    Periods(u32),
+    // Jumlah per tahun masa kerja dalam denominasi gaji employee (token, atau mata uang fiat
+    // untuk employee dengan `wage_currency`), dihitung pro-rata per detik
+    PerYearOfService(i128),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    // Bisa add/update/freeze/activate/remove employee dan menjalankan payroll
+    PayrollAdmin,
+    // Bisa deposit dan withdraw_surplus
+    Treasury,
+    // Read-only, tidak punya hak mutasi
+    Auditor,
 }
 
 #[contracttype]
@@ -51,30 +279,253 @@ pub enum DataKey {
     Token,
     Employee(Address),
     EmployeeList,
+    Role(Address, Role),
+    PendingEmployer,
+    SchemaVersion,
+    // Pra-v9 (satu token); `Totals`, `ShortfallSince`, `DebtList` dan `TotalDebt` hanya
+    // dibaca `migrate` lalu dipindah ke key `Token*` per token
+    Totals,
+    RunwayThreshold,
+    ShortfallSince,
+    WageDebt(Address),
+    DebtList,
+    TotalDebt,
+    InactivityWindow,
+    LastEmployerActivity,
+    WindDown,
+    Closed,
+    Paused,
+    LeavePolicy,
+    LeaveBalance(Address),
+    RateHistory(Address),
+    WageProposal(Address),
+    Offer(Address),
+    NoticePeriod,
+    // Timestamp akhir masa notice untuk employee yang sudah resign
+    Resignation(Address),
+    SeverancePolicy,
+    // Override kebijakan pesangon per employee
+    Severance(Address),
+    // Pesangon yang sudah dibayar; mencegah pembayaran ganda lewat freeze/activate berulang
+    SeverancePaid(Address),
+    // Token yang boleh disimpan treasury dan dipakai membayar employee
+    AllowedTokens,
+    TokenTotals(Address),
+    TokenShortfall(Address),
+    TokenDebtList(Address),
+    TokenDebt(Address),
+    Oracle,
+    // Agregat untuk employee yang gajinya dalam mata uang fiat dan dibayar dengan token ini
+    FiatTotals(Symbol, Address),
+    FiatCurrencies(Address),
+    // Kurs terakhir yang lolos cek deviasi, per pasangan mata uang/token
+    FxRate(Symbol, Address),
+    TokenMigration,
+    AutoTopUp(Address),
+    // Kumulatif dana dari satu funder, per token
+    FunderTotal(Address, Address),
+    FundingRestriction(Address),
+    // Sisa dana terbatas milik funder (funder, token) yang belum dipakai membayar gaji
+    RestrictedBalance(Address, Address),
+    RestrictedFunders(Address),
+    RestrictedTotal(Address),
+    // Token yang masih punya dana terbatas, termasuk token lama setelah migrasi token utama
+    RestrictedTokens,
+    CostCentre(Address),
+    // Sejak kapan agregat menghitung rate employee yang `start_at`-nya masih di depan
+    PreStart(Address),
+    // Sampai kapan kelebihan agregat selama cuti unpaid sudah dikoreksi
+    UnpaidLeaveSettled(Address),
+}
+
+// Tipe dan antarmuka oracle harga gaya SEP-40; hanya bagian yang dipakai payroll
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    fn base(env: Env) -> Asset;
+    fn decimals(env: Env) -> u32;
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+}
+
+// Dana dari funder ini hanya boleh dipakai membayar employee yang terdaftar di sini, atau yang
+// cost centre-nya terdaftar
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundingRestriction {
+    pub employees: Vec<Address>,
+    pub cost_centres: Vec<Symbol>,
+}
+
+// Top-up otomatis dari allowance employer (token.approve ke kontrak ini), dibatasi per periode
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AutoTopUp {
+    pub max_per_period: i128,
+    pub period_seconds: u64,
+    pub window_start: u64,
+    pub pulled_in_window: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleConfig {
+    pub oracle: Address,
+    // Harga yang lebih tua dari ini ditolak
+    pub max_age_seconds: u64,
+    // Batas perubahan kurs terhadap kurs terakhir yang diterima, dalam basis point
+    pub max_deviation_bps: u32,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BatchPayResult {
     pub paid_count: u32,
+    // Jumlah mentah lintas token; hanya bermakna kalau batch memakai satu token
     pub total_amount: i128,
+    // Total yang dibayar per token, dalam satuan token masing-masing
+    pub paid_by_token: Map<Address, i128>,
+}
+
+// Agregat payroll supaya total kewajiban bisa dihitung O(1) tanpa iterasi EmployeeList.
+// Rate per detik dibulatkan ke atas, jadi angka ini tidak pernah di bawah kewajiban sebenarnya.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayrollTotals {
+    pub rate_per_second_scaled: i128,
+    pub settled_accrued: i128,
+    pub checkpoint_timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WindDownPool {
+    pub balance: i128,
+    pub total_owed: i128,
+    // Kurs fiat -> token yang dipakai untuk `total_owed`; klaim memakai kurs yang sama
+    pub fx_rates: Map<Symbol, i128>,
+    // Bagian `total_owed` yang sudah diklaim employee
+    pub claimed_owed: i128,
+}
+
+// Snapshot saat dead man's switch terpicu; klaim employee dihitung pro-rata dari sini,
+// terpisah untuk setiap token
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WindDown {
+    pub stopped_at: u64,
+    pub pools: Map<Address, WindDownPool>,
+}
+
+// Jumlah per token
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CloseOutSummary {
+    pub paid_count: u32,
+    pub total_paid: Map<Address, i128>,
+    pub surplus_returned: Map<Address, i128>,
 }
 
 #[contract]
 pub struct FairWage;
 
 impl FairWage {
-    fn i128_to_u64(v: i128) -> Result<u64, Error> {
-        u64::try_from(v).map_err(|_| Error::Overflow)
+    fn validate_wage_period(wage_period: WagePeriod) -> Result<(), Error> {
+        if wage_period == WagePeriod::Custom(0) {
+            return Err(Error::InvalidWagePeriod);
+        }
+        Ok(())
+    }
+
+    // Kode u32 lama (sebelum schema v5); nilai lain dulu diperlakukan sebagai harian
+    fn wage_period_from_legacy(code: u32) -> WagePeriod {
+        match code {
+            0 => WagePeriod::Hour,
+            2 => WagePeriod::Week,
+            3 => WagePeriod::Month,
+            _ => WagePeriod::Day,
+        }
+    }
+
+    // Tanggal kalender (UTC) dari jumlah hari sejak 1970-01-01 (algoritma civil_from_days)
+    fn civil_from_days(days: i64) -> (i64, u32) {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        (year, month as u32)
+    }
+
+    fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+        let y = if month <= 2 { year - 1 } else { year };
+        let era = y.div_euclid(400);
+        let yoe = y.rem_euclid(400);
+        let m = i64::from(month);
+        let mp = if m > 2 { m - 3 } else { m + 9 };
+        let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    fn day_start_seconds(days: i64) -> u64 {
+        u64::try_from(days).unwrap_or(0).saturating_mul(DAY_SECONDS)
+    }
+
+    // Segmen dengan panjang periode konstan yang memuat `t`: (akhir segmen, panjang periode)
+    fn period_segment(wage_period: WagePeriod, t: u64) -> (u64, u64) {
+        let fixed = |len: u64| (u64::MAX, len);
+        let days = i64::try_from(t / DAY_SECONDS).unwrap_or(i64::MAX);
+        match wage_period {
+            WagePeriod::Hour => fixed(HOUR_SECONDS),
+            WagePeriod::Day => fixed(DAY_SECONDS),
+            WagePeriod::Week => fixed(WEEK_SECONDS),
+            WagePeriod::Biweekly => fixed(2 * WEEK_SECONDS),
+            WagePeriod::Month => fixed(MONTH_SECONDS),
+            WagePeriod::Custom(seconds) => fixed(seconds),
+            WagePeriod::CalendarMonth | WagePeriod::SemiMonthly => {
+                let (year, month) = Self::civil_from_days(days);
+                let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+                let start = Self::day_start_seconds(Self::days_from_civil(year, month, 1));
+                let end = Self::day_start_seconds(Self::days_from_civil(next_year, next_month, 1));
+                let len = end - start;
+                if wage_period == WagePeriod::SemiMonthly {
+                    (end, len / 2)
+                } else {
+                    (end, len)
+                }
+            }
+            WagePeriod::Year => {
+                let (year, _) = Self::civil_from_days(days);
+                let start = Self::day_start_seconds(Self::days_from_civil(year, 1, 1));
+                let end = Self::day_start_seconds(Self::days_from_civil(year + 1, 1, 1));
+                (end, end - start)
+            }
+        }
     }
 
-    fn get_period_seconds(wage_period: u32) -> u64 {
+    // Periode terpendek yang mungkin, supaya rate agregat tidak pernah di bawah rate sebenarnya
+    fn min_period_seconds(wage_period: WagePeriod) -> u64 {
         match wage_period {
-            WAGE_PERIOD_HOUR => HOUR_SECONDS,
-            WAGE_PERIOD_DAY => DAY_SECONDS,
-            WAGE_PERIOD_WEEK => WEEK_SECONDS,
-            WAGE_PERIOD_MONTH => MONTH_SECONDS,
-            _ => DAY_SECONDS,
+            WagePeriod::CalendarMonth => MIN_CALENDAR_MONTH_SECONDS,
+            WagePeriod::SemiMonthly => MIN_CALENDAR_MONTH_SECONDS / 2,
+            WagePeriod::Year => YEAR_SECONDS,
+            other => Self::period_segment(other, 0).1,
         }
     }
 
@@ -92,6 +543,17 @@ impl FairWage {
             .ok_or(Error::TokenNotConfigured)
     }
 
+    fn read_allowed_tokens(env: &Env) -> Vec<Address> {
+        env.storage().instance().get(&DataKey::AllowedTokens).unwrap_or(Vec::new(env))
+    }
+
+    fn require_allowed_token(env: &Env, token: &Address) -> Result<(), Error> {
+        if !Self::read_allowed_tokens(env).contains(token) {
+            return Err(Error::TokenNotAllowed);
+        }
+        Ok(())
+    }
+
     fn read_employee(env: &Env, addr: &Address) -> Result<Employee, Error> {
         env.storage()
             .persistent()
@@ -108,295 +570,2625 @@ impl FairWage {
     fn require_employer_auth(env: &Env) -> Result<Address, Error> {
         let employer = Self::read_employer(env)?;
         employer.require_auth();
+        Self::touch_employer_activity(env)?;
         Ok(employer)
     }
 
-    fn calculate_accrued(employee: &Employee, current_timestamp: u64) -> Result<i128, Error> {
+    // Kapan stream berhenti karena employer tidak aktif, kalau dead man's switch sudah lewat
+    fn stream_stop_at(env: &Env) -> Option<u64> {
+        if let Some(wd) = env.storage().instance().get::<_, WindDown>(&DataKey::WindDown) {
+            return Some(wd.stopped_at);
+        }
+        let window: u64 = env.storage().instance().get(&DataKey::InactivityWindow).unwrap_or(0);
+        if window == 0 {
+            return None;
+        }
+        let last: u64 = env.storage().instance().get(&DataKey::LastEmployerActivity)?;
+        let deadline = last.saturating_add(window);
+        if env.ledger().timestamp() > deadline {
+            return Some(deadline);
+        }
+        None
+    }
+
+    fn cap_to_stream_stop(env: &Env, timestamp: u64) -> u64 {
+        match Self::stream_stop_at(env) {
+            Some(stop) => timestamp.min(stop),
+            None => timestamp,
+        }
+    }
+
+    // Timestamp yang dipakai untuk semua perhitungan accrual
+    fn accrual_now(env: &Env) -> u64 {
+        Self::cap_to_stream_stop(env, env.ledger().timestamp())
+    }
+
+    // Pause hanya menahan perpindahan dana; accrual tetap berjalan
+    fn require_not_paused(env: &Env) -> Result<(), Error> {
+        if env.storage().instance().get(&DataKey::Paused).unwrap_or(false) {
+            return Err(Error::Paused);
+        }
+        Ok(())
+    }
+
+    fn require_open(env: &Env) -> Result<(), Error> {
+        if env.storage().instance().get(&DataKey::Closed).unwrap_or(false) {
+            return Err(Error::PayrollClosed);
+        }
+        Ok(())
+    }
+
+    fn require_not_wound_down(env: &Env) -> Result<(), Error> {
+        if Self::stream_stop_at(env).is_some() {
+            return Err(Error::PayrollWoundDown);
+        }
+        Ok(())
+    }
+
+    // Setiap panggilan employer/role yang terautentikasi me-reset dead man's switch
+    fn touch_employer_activity(env: &Env) -> Result<(), Error> {
+        Self::require_not_wound_down(env)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::LastEmployerActivity, &env.ledger().timestamp());
+        Ok(())
+    }
+
+    // Kunci status wind-down: hentikan agregat rate dan simpan snapshot saldo vs kewajiban
+    fn latch_wind_down(env: &Env) -> Result<WindDown, Error> {
+        if let Some(wd) = env.storage().instance().get::<_, WindDown>(&DataKey::WindDown) {
+            return Ok(wd);
+        }
+        let stopped_at = Self::stream_stop_at(env).ok_or(Error::EmployerStillActive)?;
+        let allowed = Self::read_allowed_tokens(env);
+
+        // Kewajiban dihitung per employee dengan pembulatan yang sama seperti klaim, supaya
+        // cuti tanpa gaji, kontrak yang sudah habis, atau employee yang belum mulai tidak ikut
+        // terhitung. Kewajiban fiat dikonversi dengan kurs saat wind-down terkunci; kalau oracle
+        // sudah tidak bisa dipakai, kurs terakhir yang lolos cek yang dipakai.
+        let mut owed_by_token: Map<Address, i128> = Map::new(env);
+        let mut fx_by_token: Map<Address, Map<Symbol, i128>> = Map::new(env);
+        let list: Vec<Address> = env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(env));
+        for addr in list.iter() {
+            let emp = match Self::read_employee(env, &addr) { Ok(v) => v, Err(_) => continue };
+            if !allowed.contains(&emp.token) {
+                continue;
+            }
+            let accrued = emp
+                .accrued_balance
+                .checked_add(Self::calculate_accrued(&emp, stopped_at)?)
+                .ok_or(Error::Overflow)?;
+            let mut fx_rates = fx_by_token.get(emp.token.clone()).unwrap_or(Map::new(env));
+            let accrued_payout = match &emp.wage_currency {
+                Some(currency) if accrued > 0 => {
+                    let rate = match fx_rates.get(currency.clone()) {
+                        Some(rate) => rate,
+                        None => {
+                            let rate = Self::fx_rate_or_last(env, currency, &emp.token)?;
+                            fx_rates.set(currency.clone(), rate);
+                            fx_by_token.set(emp.token.clone(), fx_rates);
+                            rate
+                        }
+                    };
+                    accrued.checked_mul(rate).ok_or(Error::Overflow)? / RATE_SCALE
+                }
+                _ => accrued,
+            };
+            let debt: i128 = env.storage().persistent().get(&DataKey::WageDebt(addr.clone())).unwrap_or(0);
+            let owed = owed_by_token.get(emp.token.clone()).unwrap_or(0);
+            owed_by_token.set(
+                emp.token.clone(),
+                owed.checked_add(accrued_payout).and_then(|v| v.checked_add(debt)).ok_or(Error::Overflow)?,
+            );
+        }
+
+        // Dana terbatas bukan milik pool klaim; kembalikan ke funder sebelum saldo di-snapshot
+        Self::refund_restricted(env)?;
+
+        let mut pools = Map::new(env);
+        for token in allowed.iter() {
+            for key in Self::totals_keys(env, &token).iter() {
+                let mut totals = Self::read_totals(env, &key);
+                Self::roll_totals(&mut totals, stopped_at)?;
+                totals.rate_per_second_scaled = 0;
+                env.storage().instance().set(&key, &totals);
+            }
+
+            let pool = WindDownPool {
+                balance: Self::contract_token_balance(env, &token),
+                total_owed: owed_by_token.get(token.clone()).unwrap_or(0),
+                fx_rates: fx_by_token.get(token.clone()).unwrap_or(Map::new(env)),
+                claimed_owed: 0,
+            };
+            env.events().publish(
+                (Symbol::new(env, "wind_down"), &token),
+                (stopped_at, pool.balance, pool.total_owed),
+            );
+            pools.set(token, pool);
+        }
+
+        let wd = WindDown { stopped_at, pools };
+        env.storage().instance().set(&DataKey::WindDown, &wd);
+        Ok(wd)
+    }
+
+    fn has_role_internal(env: &Env, account: &Address, role: Role) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Role(account.clone(), role))
+            .unwrap_or(false)
+    }
+
+    // Employer (owner) selalu lolos; address lain harus punya role yang diminta
+    fn require_role(env: &Env, caller: &Address, role: Role) -> Result<(), Error> {
+        caller.require_auth();
+        let employer = Self::read_employer(env)?;
+        if *caller == employer || Self::has_role_internal(env, caller, role) {
+            return Self::touch_employer_activity(env);
+        }
+        Err(Error::NotAuthorized)
+    }
+
+    fn read_schema_version(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::SchemaVersion)
+            .unwrap_or(1)
+    }
+
+    fn legacy_field<T: TryFromVal<Env, Val>>(env: &Env, raw: &Map<Symbol, Val>, name: &str) -> Option<T> {
+        let v = raw.get(Symbol::new(env, name))?;
+        T::try_from_val(env, &v).ok()
+    }
+
+    // Bangun ulang `Employee` dari record mentah versi lama; field yang belum ada diisi default
+    fn migrate_employee_record(env: &Env, raw: &Map<Symbol, Val>, now: u64) -> Result<Employee, Error> {
+        let wage_rate: i128 = Self::legacy_field(env, raw, "wage_rate").ok_or(Error::InvalidEmployeeRecord)?;
+        let wage_period: WagePeriod = match Self::legacy_field::<WagePeriod>(env, raw, "wage_period") {
+            Some(p) => p,
+            None => Self::wage_period_from_legacy(Self::legacy_field(env, raw, "wage_period").unwrap_or(1)),
+        };
+        let accrued_balance: i128 = Self::legacy_field(env, raw, "accrued_balance").unwrap_or(0);
+        let accrual_remainder: i128 = Self::legacy_field(env, raw, "accrual_remainder").unwrap_or(0);
+        let active: bool = Self::legacy_field(env, raw, "active").unwrap_or(true);
+        let mut last_accrual_timestamp: u64 =
+            Self::legacy_field(env, raw, "last_accrual_timestamp").unwrap_or(0);
+        // Sama seperti fix_employee_timestamp: record lama kadang tersimpan dengan timestamp 0
+        if last_accrual_timestamp == 0 {
+            last_accrual_timestamp = now;
+        }
+        // Record sebelum v3 tidak mencatat kapan di-freeze; pakai titik accrual terakhir
+        let default_suspended_at = if active { 0 } else { last_accrual_timestamp };
+        let suspended_at: u64 = Self::legacy_field(env, raw, "suspended_at").unwrap_or(default_suspended_at);
+        let total_suspended_seconds: u64 = Self::legacy_field(env, raw, "total_suspended_seconds").unwrap_or(0);
+        let leave_windows: Vec<LeaveWindow> = Self::legacy_field(env, raw, "leave_windows").unwrap_or(Vec::new(env));
+        let start_at: u64 = Self::legacy_field(env, raw, "start_at").unwrap_or(last_accrual_timestamp);
+        let end_at: Option<u64> = Self::legacy_field(env, raw, "end_at").unwrap_or(None);
+        let ended: bool = Self::legacy_field(env, raw, "ended").unwrap_or(false);
+        let scheduled_rates: Vec<RateChange> = Self::legacy_field(env, raw, "scheduled_rates").unwrap_or(Vec::new(env));
+        let token: Address = match Self::legacy_field(env, raw, "token") {
+            Some(token) => token,
+            None => Self::read_token(env)?,
+        };
+        let wage_currency: Option<Symbol> = Self::legacy_field(env, raw, "wage_currency").unwrap_or(None);
+        Ok(Employee {
+            wage_rate,
+            last_accrual_timestamp,
+            wage_period,
+            accrued_balance,
+            accrual_remainder,
+            active,
+            suspended_at,
+            total_suspended_seconds,
+            leave_windows,
+            start_at,
+            end_at,
+            ended,
+            scheduled_rates,
+            token,
+            wage_currency,
+        })
+    }
+
+    // Accrual sejak checkpoint terakhir: (unit utuh, sisa pecahan baru).
+    // Pembagian dilakukan sekali per segmen dengan sisa yang dibawa, jadi hasilnya sama persis
+    // berapa kali pun di-checkpoint di tengah jalan.
+    fn accrue_exact(employee: &Employee, current_timestamp: u64) -> Result<(i128, i128), Error> {
         // Employee non-aktif tidak mendapat accrued wages
         if !employee.active {
-            return Ok(0);
+            return Ok((0, employee.accrual_remainder));
         }
+        let current_timestamp = match employee.end_at {
+            Some(end_at) => current_timestamp.min(end_at),
+            None => current_timestamp,
+        };
         if employee.last_accrual_timestamp == 0 || current_timestamp <= employee.last_accrual_timestamp {
-            return Ok(0);
+            return Ok((0, employee.accrual_remainder));
         }
-        let elapsed = current_timestamp - employee.last_accrual_timestamp;
-        let period = Self::get_period_seconds(employee.wage_period);
-        let num = employee
-            .wage_rate
-            .checked_mul(i128::from(elapsed))
-            .ok_or(Error::Overflow)?;
-        Ok(num / i128::from(period))
+        // Pecah rentang waktu per segmen yang panjang periodenya konstan (bulan/tahun kalender)
+        let mut units: i128 = 0;
+        let mut carry = employee.accrual_remainder;
+        let mut t = employee.last_accrual_timestamp;
+        let mut carry_period = Self::period_segment(employee.wage_period, t).1;
+        while t < current_timestamp {
+            let (segment_end, period) = Self::period_segment(employee.wage_period, t);
+            carry = Self::rescale_remainder(carry, carry_period, period)?;
+            // Segmen juga dipotong di setiap perubahan rate terjadwal
+            let end = segment_end
+                .min(Self::next_rate_change_after(employee, t).unwrap_or(u64::MAX))
+                .min(current_timestamp);
+            let elapsed = (end - t).saturating_sub(Self::unpaid_leave_overlap(employee, t, end));
+            let num = Self::rate_at(employee, t)
+                .checked_mul(i128::from(elapsed))
+                .ok_or(Error::Overflow)?
+                .checked_add(carry)
+                .ok_or(Error::Overflow)?;
+            units = units.checked_add(num / i128::from(period)).ok_or(Error::Overflow)?;
+            carry = num % i128::from(period);
+            carry_period = period;
+            t = end;
+        }
+        // Kalau berhenti tepat di batas bulan/tahun, sisa harus dalam satuan segmen berikutnya
+        let next_period = Self::period_segment(employee.wage_period, current_timestamp).1;
+        Ok((units, Self::rescale_remainder(carry, carry_period, next_period)?))
     }
 
-    fn apply_withdrawal(employee_data: &mut Employee, amount: i128, now: u64) -> Result<(), Error> {
-        if amount <= 0 {
-            return Err(Error::InvalidAmount);
+    // Rate yang berlaku pada waktu `t`, termasuk perubahan terjadwal yang sudah efektif
+    fn rate_at(employee: &Employee, t: u64) -> i128 {
+        let mut rate = employee.wage_rate;
+        for change in employee.scheduled_rates.iter() {
+            if change.effective_at > t {
+                break;
+            }
+            rate = change.wage_rate;
         }
-        let current_accrual = Self::calculate_accrued(employee_data, now)?;
-        let available = employee_data
-            .accrued_balance
-            .checked_add(current_accrual)
-            .ok_or(Error::Overflow)?;
-        if amount > available {
-            return Err(Error::WithdrawalExceedsAccrued);
+        rate
+    }
+
+    fn next_rate_change_after(employee: &Employee, t: u64) -> Option<u64> {
+        employee
+            .scheduled_rates
+            .iter()
+            .map(|change| change.effective_at)
+            .find(|effective_at| *effective_at > t)
+    }
+
+    // Pindahkan perubahan rate yang sudah ter-accrue ke `wage_rate` supaya jadwal tetap pendek
+    fn fold_applied_rates(env: &Env, employee: &mut Employee) {
+        let mut remaining = Vec::new(env);
+        for change in employee.scheduled_rates.iter() {
+            if change.effective_at <= employee.last_accrual_timestamp {
+                employee.wage_rate = change.wage_rate;
+            } else {
+                remaining.push_back(change);
+            }
         }
+        employee.scheduled_rates = remaining;
+    }
 
-        if amount <= employee_data.accrued_balance {
-            employee_data.accrued_balance = employee_data
-                .accrued_balance
-                .checked_sub(amount)
-                .ok_or(Error::Overflow)?;
-            return Ok(());
+    // Terapkan rate/periode baru mulai `now`; accrual lama di-checkpoint dulu
+    fn apply_wage_terms(
+        env: &Env,
+        addr: &Address,
+        data: &mut Employee,
+        new_wage_rate: i128,
+        new_wage_period: WagePeriod,
+        now: u64,
+    ) -> Result<(), Error> {
+        Self::settle_accrual(data, now)?;
+        Self::settle_overcount(env, addr, data, now)?;
+        let old_rate = Self::employee_rate_per_second_scaled(data)?;
+        Self::fold_applied_rates(env, data);
+        if new_wage_period != data.wage_period {
+            data.accrual_remainder = Self::rescale_remainder(
+                data.accrual_remainder,
+                Self::period_segment(data.wage_period, now).1,
+                Self::period_segment(new_wage_period, now).1,
+            )?;
+            data.wage_period = new_wage_period;
+        }
+        data.wage_rate = new_wage_rate;
+        Self::write_employee(env, addr, data);
+        if data.active {
+            let new_rate = Self::employee_rate_per_second_scaled(data)?;
+            Self::adjust_totals(env, &Self::totals_key(data), now, new_rate.checked_sub(old_rate).ok_or(Error::Overflow)?, 0)?;
         }
+        Self::push_rate_history(env, addr, RateChange { effective_at: now, wage_rate: new_wage_rate });
+        Ok(())
+    }
 
-        let from_current = amount
-            .checked_sub(employee_data.accrued_balance)
-            .ok_or(Error::Overflow)?;
-        employee_data.accrued_balance = 0;
+    fn propose_wage_terms(
+        env: &Env,
+        addr: &Address,
+        wage_rate: i128,
+        wage_period: WagePeriod,
+        now: u64,
+    ) -> WageProposal {
+        let proposal = WageProposal {
+            wage_rate,
+            wage_period,
+            proposed_at: now,
+            expires_at: now.saturating_add(WAGE_PROPOSAL_TTL_SECONDS),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::WageProposal(addr.clone()), &proposal);
+        env.events().publish((symbol_short!("wage_prop"), addr), proposal.clone());
+        proposal
+    }
 
-        let period_seconds = Self::get_period_seconds(employee_data.wage_period);
-        let sec_num = i128::from(period_seconds)
-            .checked_mul(from_current)
-            .ok_or(Error::Overflow)?;
-        let seconds_paid_i128 = sec_num
-            .checked_div(employee_data.wage_rate)
-            .ok_or(Error::Overflow)?;
-        let seconds_paid = Self::i128_to_u64(seconds_paid_i128)?;
+    // Entri dengan effective_at yang sama diganti, sisanya tetap urut naik
+    fn push_rate_history(env: &Env, addr: &Address, change: RateChange) {
+        let key = DataKey::RateHistory(addr.clone());
+        let history: Vec<RateChange> = env.storage().persistent().get(&key).unwrap_or(Vec::new(env));
+        let mut updated = Vec::new(env);
+        let mut inserted = false;
+        for existing in history.iter() {
+            if !inserted && change.effective_at <= existing.effective_at {
+                updated.push_back(change.clone());
+                inserted = true;
+            }
+            if existing.effective_at != change.effective_at {
+                updated.push_back(existing);
+            }
+        }
+        if !inserted {
+            updated.push_back(change);
+        }
+        env.storage().persistent().set(&key, &updated);
+    }
 
-        employee_data.last_accrual_timestamp = employee_data
-            .last_accrual_timestamp
-            .checked_add(seconds_paid)
-            .ok_or(Error::Overflow)?;
+    fn rescale_remainder(remainder: i128, from_period: u64, to_period: u64) -> Result<i128, Error> {
+        if from_period == to_period {
+            return Ok(remainder);
+        }
+        let num = remainder.checked_mul(i128::from(to_period)).ok_or(Error::Overflow)?;
+        Ok(num / i128::from(from_period))
+    }
+
+    // Agregat langsung menghitung rate employee yang di-hire dengan `start_at` di masa depan.
+    // Koreksi kelebihan sebelum start_at setiap kali rate/token employee berubah dan saat
+    // employee disentuh setelah mulai, sama seperti koreksi `end_at` di `end_term_if_due`.
+    fn settle_pre_start(env: &Env, addr: &Address, employee: &Employee, now: u64) -> Result<(), Error> {
+        let key = DataKey::PreStart(addr.clone());
+        let since: u64 = match env.storage().persistent().get(&key) {
+            Some(since) => since,
+            None => return Ok(()),
+        };
+        let until = now.min(employee.start_at);
+        if employee.active && until > since {
+            let overcounted = Self::employee_rate_per_second_scaled(employee)?
+                .checked_mul(i128::from(until - since))
+                .ok_or(Error::Overflow)?
+                / RATE_SCALE;
+            Self::adjust_totals(env, &Self::totals_key(employee), now, 0, -overcounted)?;
+        }
+        if now >= employee.start_at {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &now);
+        }
         Ok(())
     }
-}
 
-#[contractimpl]
-impl FairWage {
-    pub fn initialize(env: Env, employer: Address, token_address: Address) -> Result<(), Error> {
-        employer.require_auth();
-        if env.storage().instance().has(&DataKey::Employer) {
-            return Err(Error::AlreadyInitialized);
+    // Agregat juga tetap menghitung rate employee selama cuti unpaid. Kelebihannya dikoreksi
+    // dengan pola yang sama seperti `settle_pre_start`, sampai `now` atau `end_at`.
+    fn settle_unpaid_leave(env: &Env, addr: &Address, employee: &Employee, now: u64) -> Result<(), Error> {
+        let key = DataKey::UnpaidLeaveSettled(addr.clone());
+        let since: u64 = match env.storage().persistent().get(&key) {
+            Some(since) => since,
+            None => return Ok(()),
+        };
+        let until = employee.end_at.map_or(now, |end_at| now.min(end_at));
+        if employee.active && until > since {
+            let overlap = Self::unpaid_leave_overlap(employee, since, until);
+            if overlap > 0 {
+                let overcounted = Self::employee_rate_per_second_scaled(employee)?
+                    .checked_mul(i128::from(overlap))
+                    .ok_or(Error::Overflow)?
+                    / RATE_SCALE;
+                Self::adjust_totals(env, &Self::totals_key(employee), now, 0, -overcounted)?;
+            }
         }
-        env.storage().instance().set(&DataKey::Employer, &employer);
-        env.storage().instance().set(&DataKey::Token, &token_address);
-        env.storage().persistent().set(&DataKey::EmployeeList, &Vec::<Address>::new(&env));
+        let pending = employee
+            .leave_windows
+            .iter()
+            .any(|w| w.leave_type == LeaveType::Unpaid && w.end > now);
+        if pending {
+            env.storage().persistent().set(&key, &now);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    // Koreksi semua kelebihan agregat yang tertunda; dipanggil setiap kali employee disentuh
+    fn settle_overcount(env: &Env, addr: &Address, employee: &Employee, now: u64) -> Result<(), Error> {
+        Self::settle_pre_start(env, addr, employee, now)?;
+        Self::settle_unpaid_leave(env, addr, employee, now)
+    }
+
+    // Tandai kontrak fixed-term yang sudah lewat `end_at`: accrual dikunci sampai `end_at`,
+    // employee jadi non-aktif, dan rate-nya dikeluarkan dari agregat. Caller yang menulis record.
+    fn end_term_if_due(env: &Env, addr: &Address, employee: &mut Employee, now: u64) -> Result<bool, Error> {
+        Self::settle_overcount(env, addr, employee, now)?;
+        let end_at = match employee.end_at {
+            Some(end_at) if !employee.ended && now >= end_at => end_at,
+            _ => return Ok(false),
+        };
+        Self::settle_accrual(employee, end_at)?;
+        if employee.active {
+            // Agregat masih menghitung rate ini sejak end_at; koreksi kelebihannya
+            let rate = Self::employee_rate_per_second_scaled(employee)?;
+            let overcounted = rate
+                .checked_mul(i128::from(now - end_at))
+                .ok_or(Error::Overflow)?
+                / RATE_SCALE;
+            Self::adjust_totals(env, &Self::totals_key(employee), now, -rate, -overcounted)?;
+            employee.suspended_at = end_at;
+        }
+        employee.active = false;
+        employee.ended = true;
+        env.events().publish((symbol_short!("ended"), addr), end_at);
+        Ok(true)
+    }
+
+    fn read_severance_policy(env: &Env, addr: &Address) -> SeverancePolicy {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Severance(addr.clone()))
+            .or_else(|| env.storage().instance().get(&DataKey::SeverancePolicy))
+            .unwrap_or(SeverancePolicy::None)
+    }
+
+    fn validate_severance_policy(policy: &SeverancePolicy) -> Result<(), Error> {
+        match policy {
+            SeverancePolicy::PerYearOfService(amount) if *amount < 0 => Err(Error::InvalidAmount),
+            _ => Ok(()),
+        }
+    }
+
+    // Pesangon yang terutang kalau hubungan kerja diputus di `now`. Employee yang resign
+    // atau kontraknya selesai sendiri tidak dapat pesangon, begitu juga yang sudah dibayar.
+    fn severance_due(env: &Env, addr: &Address, employee: &Employee, now: u64) -> Result<i128, Error> {
+        if employee.ended
+            || env.storage().persistent().has(&DataKey::Resignation(addr.clone()))
+            || env.storage().persistent().has(&DataKey::SeverancePaid(addr.clone()))
+        {
+            return Ok(0);
+        }
+        // Masa kerja berhenti saat freeze dan tidak menghitung waktu suspend sebelumnya
+        let mut tenure_end = if employee.suspended_at != 0 { employee.suspended_at } else { now };
+        if let Some(end_at) = employee.end_at {
+            tenure_end = tenure_end.min(end_at);
+        }
+        let tenure = tenure_end
+            .saturating_sub(employee.start_at)
+            .saturating_sub(employee.total_suspended_seconds);
+
+        match Self::read_severance_policy(env, addr) {
+            SeverancePolicy::None => Ok(0),
+            SeverancePolicy::Periods(periods) => Self::rate_at(employee, tenure_end)
+                .checked_mul(i128::from(periods))
+                .ok_or(Error::Overflow),
+            SeverancePolicy::PerYearOfService(amount) => Ok(amount
+                .checked_mul(i128::from(tenure))
+                .ok_or(Error::Overflow)?
+                / i128::from(YEAR_SECONDS)),
+        }
+    }
+
+    // Bayar pesangon dari surplus treasury; tidak boleh memakai dana gaji employee lain
+    fn pay_severance(env: &Env, addr: &Address, employee: &Employee, now: u64) -> Result<i128, Error> {
+        let amount = Self::severance_due(env, addr, employee, now)?;
+        if amount <= 0 {
+            return Ok(0);
+        }
+        let payout = Self::payout_amount(env, employee, amount)?;
+        let token_client = token::Client::new(env, &employee.token);
+        let contract_balance = token_client.balance(&env.current_contract_address());
+        // Kewajiban fiat employee lain boleh memakai kurs terakhir yang lolos cek, supaya oracle
+        // yang basi untuk mata uang lain tidak menahan pesangon
+        let liabilities = Self::combine_totals_with(env, &employee.token, now, |currency| {
+            Self::fx_rate_or_last(env, currency, &employee.token)
+        })?
+        .settled_accrued;
+        if payout > Self::surplus_over(env, &employee.token, contract_balance, liabilities)? {
+            return Err(Error::InsufficientContractBalance);
+        }
+
+        token_client.transfer(&env.current_contract_address(), addr, &payout);
+        env.storage().persistent().set(&DataKey::SeverancePaid(addr.clone()), &amount);
+        env.events().publish((symbol_short!("severance"), addr), payout);
+        Ok(amount)
+    }
+
+    // Bayar seluruh `accrued_balance` (yang sudah di-settle) ke employee sekaligus, atau catat
+    // sebagai utang gaji kalau token itu sedang insolvent. Mengembalikan jumlah dalam denominasi
+    // gaji employee, untuk koreksi agregat.
+    fn pay_final_balance(env: &Env, addr: &Address, employee: &mut Employee) -> Result<i128, Error> {
+        let total_owed = employee.accrued_balance;
+        if total_owed <= 0 {
+            return Ok(0);
+        }
+        let payout = Self::payout_amount(env, employee, total_owed)?;
+        employee.accrued_balance = 0;
+        // Selama antrean utang gaji belum lunas, final pay ikut antre sebagai utang gaji supaya
+        // tidak menyalip employee lain; dibayar saat `settle_wage_debts`
+        if Self::read_total_debt(env, &employee.token) > 0 {
+            Self::add_wage_debt(env, &employee.token, addr, payout)?;
+            Self::adjust_totals(
+                env,
+                &DataKey::TokenTotals(employee.token.clone()),
+                Self::accrual_now(env),
+                0,
+                payout,
+            )?;
+            env.events().publish((symbol_short!("final_dbt"), addr), payout);
+            return Ok(total_owed);
+        }
+        Self::pay_out(env, &employee.token, addr, payout)?;
+        env.events().publish((symbol_short!("final_pay"), addr), payout);
+        Ok(total_owed)
+    }
+
+    fn calculate_accrued(employee: &Employee, current_timestamp: u64) -> Result<i128, Error> {
+        Ok(Self::accrue_exact(employee, current_timestamp)?.0)
+    }
+
+    // Pindahkan accrual berjalan ke `accrued_balance` dan geser checkpoint ke `now`.
+    // Mengembalikan saldo terutang setelah checkpoint.
+    fn settle_accrual(employee: &mut Employee, now: u64) -> Result<i128, Error> {
+        let (units, remainder) = Self::accrue_exact(employee, now)?;
+        employee.accrued_balance = employee.accrued_balance.checked_add(units).ok_or(Error::Overflow)?;
+        employee.accrual_remainder = remainder;
+        if now > employee.last_accrual_timestamp {
+            employee.last_accrual_timestamp = now;
+        }
+        Ok(employee.accrued_balance)
+    }
+
+    // Berapa detik dalam [from, to) yang jatuh di cuti unpaid
+    fn unpaid_leave_overlap(employee: &Employee, from: u64, to: u64) -> u64 {
+        let mut total: u64 = 0;
+        for w in employee.leave_windows.iter() {
+            if w.leave_type != LeaveType::Unpaid {
+                continue;
+            }
+            let start = w.start.max(from);
+            let end = w.end.min(to);
+            if end > start {
+                total = total.saturating_add(end - start);
+            }
+        }
+        total
+    }
+
+    fn read_leave_policy(env: &Env) -> LeavePolicy {
+        env.storage()
+            .instance()
+            .get(&DataKey::LeavePolicy)
+            .unwrap_or(LeavePolicy {
+                vacation_seconds_per_year: 0,
+                sick_seconds_per_year: 0,
+            })
+    }
+
+    // Saldo cuti bertambah sebanding masa kerja sejak `accrued_until`. Sama seperti masa kerja
+    // untuk pesangon, waktu freeze dan setelah `end_at` tidak dihitung.
+    fn read_leave_balance(env: &Env, addr: &Address, employee: &Employee, now: u64) -> LeaveBalance {
+        let mut balance: LeaveBalance = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LeaveBalance(addr.clone()))
+            .unwrap_or(LeaveBalance {
+                vacation_seconds: 0,
+                sick_seconds: 0,
+                unpaid_seconds_taken: 0,
+                accrued_until: now,
+            });
+        let mut earn_until = if employee.active { now } else { now.min(employee.suspended_at) };
+        if let Some(end_at) = employee.end_at {
+            earn_until = earn_until.min(end_at);
+        }
+        if earn_until > balance.accrued_until {
+            let policy = Self::read_leave_policy(env);
+            let tenure = u128::from(earn_until - balance.accrued_until);
+            let earned = |per_year: u64| -> u64 {
+                u64::try_from(u128::from(per_year) * tenure / u128::from(YEAR_SECONDS)).unwrap_or(u64::MAX)
+            };
+            balance.vacation_seconds = balance.vacation_seconds.saturating_add(earned(policy.vacation_seconds_per_year));
+            balance.sick_seconds = balance.sick_seconds.saturating_add(earned(policy.sick_seconds_per_year));
+            balance.accrued_until = earn_until;
+        }
+        balance
+    }
+
+    fn ceil_div(num: i128, den: i128) -> Result<i128, Error> {
+        let q = num.checked_div(den).ok_or(Error::Overflow)?;
+        if num % den != 0 {
+            return q.checked_add(1).ok_or(Error::Overflow);
+        }
+        Ok(q)
+    }
+
+    // Pakai rate tertinggi (sekarang atau terjadwal) supaya agregat tidak tertinggal saat kenaikan berlaku
+    fn employee_rate_per_second_scaled(employee: &Employee) -> Result<i128, Error> {
+        let mut rate = employee.wage_rate;
+        for change in employee.scheduled_rates.iter() {
+            rate = rate.max(change.wage_rate);
+        }
+        let num = rate.checked_mul(RATE_SCALE).ok_or(Error::Overflow)?;
+        Self::ceil_div(num, i128::from(Self::min_period_seconds(employee.wage_period)))
+    }
+
+    // Key agregat tempat rate dan saldo employee ini dihitung: per token, atau per
+    // pasangan mata uang/token untuk gaji fiat
+    fn totals_key(employee: &Employee) -> DataKey {
+        match &employee.wage_currency {
+            Some(currency) => DataKey::FiatTotals(currency.clone(), employee.token.clone()),
+            None => DataKey::TokenTotals(employee.token.clone()),
+        }
+    }
+
+    fn read_fiat_currencies(env: &Env, token: &Address) -> Vec<Symbol> {
+        env.storage()
+            .instance()
+            .get(&DataKey::FiatCurrencies(token.clone()))
+            .unwrap_or(Vec::new(env))
+    }
+
+    fn register_fiat_currency(env: &Env, token: &Address, currency: &Symbol) {
+        let mut currencies = Self::read_fiat_currencies(env, token);
+        if !currencies.contains(currency) {
+            currencies.push_back(currency.clone());
+            env.storage().instance().set(&DataKey::FiatCurrencies(token.clone()), &currencies);
+        }
+    }
+
+    // Semua key agregat yang dibayar dengan `token`
+    fn totals_keys(env: &Env, token: &Address) -> Vec<DataKey> {
+        let mut keys = Vec::from_array(env, [DataKey::TokenTotals(token.clone())]);
+        for currency in Self::read_fiat_currencies(env, token).iter() {
+            keys.push_back(DataKey::FiatTotals(currency, token.clone()));
+        }
+        keys
+    }
+
+    fn read_totals(env: &Env, key: &DataKey) -> PayrollTotals {
+        env.storage()
+            .instance()
+            .get(key)
+            .unwrap_or(PayrollTotals {
+                rate_per_second_scaled: 0,
+                settled_accrued: 0,
+                checkpoint_timestamp: env.ledger().timestamp(),
+            })
+    }
+
+    // Geser checkpoint ke `now`: accrual sejak checkpoint dipindah ke settled_accrued
+    fn roll_totals(totals: &mut PayrollTotals, now: u64) -> Result<(), Error> {
+        if now <= totals.checkpoint_timestamp {
+            return Ok(());
+        }
+        let elapsed = now - totals.checkpoint_timestamp;
+        let num = totals
+            .rate_per_second_scaled
+            .checked_mul(i128::from(elapsed))
+            .ok_or(Error::Overflow)?;
+        let accrued = Self::ceil_div(num, RATE_SCALE)?;
+        totals.settled_accrued = totals.settled_accrued.checked_add(accrued).ok_or(Error::Overflow)?;
+        totals.checkpoint_timestamp = now;
+        Ok(())
+    }
+
+    // Dipanggil setiap kali rate aktif berubah atau ada pembayaran keluar; `key` dari `totals_key`
+    fn adjust_totals(env: &Env, key: &DataKey, now: u64, rate_delta: i128, settled_delta: i128) -> Result<(), Error> {
+        let mut totals = Self::read_totals(env, key);
+        Self::roll_totals(&mut totals, now)?;
+        totals.rate_per_second_scaled = totals
+            .rate_per_second_scaled
+            .checked_add(rate_delta)
+            .ok_or(Error::Overflow)?
+            .max(0);
+        totals.settled_accrued = totals
+            .settled_accrued
+            .checked_add(settled_delta)
+            .ok_or(Error::Overflow)?
+            .max(0);
+        env.storage().instance().set(key, &totals);
+        Ok(())
+    }
+
+    // Hitung ulang satu agregat secara tepat dengan iterasi; hanya untuk migrate/resync
+    fn recompute_totals(env: &Env, key: &DataKey, now: u64) -> Result<PayrollTotals, Error> {
+        let list: Vec<Address> = env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(env));
+        let mut totals = PayrollTotals {
+            rate_per_second_scaled: 0,
+            settled_accrued: 0,
+            checkpoint_timestamp: now,
+        };
+        for addr in list.iter() {
+            if let Ok(emp) = Self::read_employee(env, &addr) {
+                if Self::totals_key(&emp) != *key {
+                    continue;
+                }
+                let owed = emp
+                    .accrued_balance
+                    .checked_add(Self::calculate_accrued(&emp, now)?)
+                    .ok_or(Error::Overflow)?;
+                totals.settled_accrued = totals.settled_accrued.checked_add(owed).ok_or(Error::Overflow)?;
+                // Agregat baru menghitung rate employee yang belum mulai sejak `now`
+                for marker in [DataKey::PreStart(addr.clone()), DataKey::UnpaidLeaveSettled(addr.clone())] {
+                    if env.storage().persistent().has(&marker) {
+                        env.storage().persistent().set(&marker, &now);
+                    }
+                }
+                if emp.active {
+                    totals.rate_per_second_scaled = totals
+                        .rate_per_second_scaled
+                        .checked_add(Self::employee_rate_per_second_scaled(&emp)?)
+                        .ok_or(Error::Overflow)?;
+                }
+            }
+        }
+        Ok(totals)
+    }
+
+    // Gabungan semua agregat yang dibayar dengan `token`, dalam satuan token. Bagian fiat
+    // dikonversi dengan kurs oracle saat ini dan dibulatkan ke atas.
+    fn combined_totals(env: &Env, token: &Address, now: u64) -> Result<PayrollTotals, Error> {
+        Self::combine_totals_with(env, token, now, |currency| Self::fx_rate(env, currency, token))
+    }
+
+    // Sama dengan `combined_totals`, tapi kurs tiap mata uang diambil dari `rate_of`
+    fn combine_totals_with(
+        env: &Env,
+        token: &Address,
+        now: u64,
+        mut rate_of: impl FnMut(&Symbol) -> Result<i128, Error>,
+    ) -> Result<PayrollTotals, Error> {
+        let mut combined = Self::read_totals(env, &DataKey::TokenTotals(token.clone()));
+        Self::roll_totals(&mut combined, now)?;
+        for currency in Self::read_fiat_currencies(env, token).iter() {
+            let mut fiat = Self::read_totals(env, &DataKey::FiatTotals(currency.clone(), token.clone()));
+            Self::roll_totals(&mut fiat, now)?;
+            if fiat.settled_accrued == 0 && fiat.rate_per_second_scaled == 0 {
+                continue;
+            }
+            let rate = rate_of(&currency)?;
+            let settled = Self::ceil_div(fiat.settled_accrued.checked_mul(rate).ok_or(Error::Overflow)?, RATE_SCALE)?;
+            let per_second = Self::ceil_div(fiat.rate_per_second_scaled.checked_mul(rate).ok_or(Error::Overflow)?, RATE_SCALE)?;
+            combined.settled_accrued = combined.settled_accrued.checked_add(settled).ok_or(Error::Overflow)?;
+            combined.rate_per_second_scaled = combined
+                .rate_per_second_scaled
+                .checked_add(per_second)
+                .ok_or(Error::Overflow)?;
+        }
+        Ok(combined)
+    }
+
+    // Total yang sudah menjadi hak employee (accrued_balance + accrual berjalan) dalam satu token
+    fn total_liabilities(env: &Env, token: &Address, now: u64) -> Result<i128, Error> {
+        Ok(Self::combined_totals(env, token, now)?.settled_accrued)
+    }
+
+    // Berapa detik payroll yang masih bisa ditanggung saldo setelah kewajiban saat ini
+    fn runway_seconds(env: &Env, token: &Address, contract_balance: i128) -> Result<u64, Error> {
+        if Self::stream_stop_at(env).is_some() {
+            return Ok(u64::MAX);
+        }
+        let totals = Self::combined_totals(env, token, Self::accrual_now(env))?;
+        let free = contract_balance.checked_sub(totals.settled_accrued).ok_or(Error::Overflow)?;
+        if free <= 0 {
+            return Ok(0);
+        }
+        if totals.rate_per_second_scaled <= 0 {
+            return Ok(u64::MAX);
+        }
+        let num = free.checked_mul(RATE_SCALE).ok_or(Error::Overflow)?;
+        let seconds = num / totals.rate_per_second_scaled;
+        Ok(u64::try_from(seconds).unwrap_or(u64::MAX))
+    }
+
+    fn contract_token_balance(env: &Env, token: &Address) -> i128 {
+        token::Client::new(env, token).balance(&env.current_contract_address())
+    }
+
+    // Dipanggil setelah dana keluar; threshold 0 berarti peringatan dimatikan. Hanya peringatan,
+    // jadi kalau runway tidak bisa dihitung (mis. kurs oracle basi) pembayaran tetap jalan.
+    fn check_low_runway(env: &Env, token: &Address) {
+        let threshold: u64 = env.storage().instance().get(&DataKey::RunwayThreshold).unwrap_or(0);
+        if threshold == 0 {
+            return;
+        }
+        let Ok(runway) = Self::runway_seconds(env, token, Self::contract_token_balance(env, token)) else {
+            return;
+        };
+        if runway < threshold {
+            env.events().publish((Symbol::new(env, "low_runway"), token), (runway, threshold));
+        }
+    }
+
+    fn read_total_debt(env: &Env, token: &Address) -> i128 {
+        env.storage().instance().get(&DataKey::TokenDebt(token.clone())).unwrap_or(0)
+    }
+
+    // Selama masih ada utang gaji dari distribusi pro-rata, pembayaran individual di token itu ditahan
+    fn require_no_wage_debt(env: &Env, token: &Address) -> Result<(), Error> {
+        if Self::read_total_debt(env, token) > 0 {
+            return Err(Error::OutstandingWageDebt);
+        }
+        Ok(())
+    }
+
+    fn add_wage_debt(env: &Env, token: &Address, addr: &Address, amount: i128) -> Result<(), Error> {
+        let key = DataKey::WageDebt(addr.clone());
+        let current: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        if current == 0 {
+            let list_key = DataKey::TokenDebtList(token.clone());
+            let mut debtors: Vec<Address> = env.storage().persistent().get(&list_key).unwrap_or(Vec::new(env));
+            debtors.push_back(addr.clone());
+            env.storage().persistent().set(&list_key, &debtors);
+        }
+        env.storage().persistent().set(&key, &current.checked_add(amount).ok_or(Error::Overflow)?);
+        let total = Self::read_total_debt(env, token).checked_add(amount).ok_or(Error::Overflow)?;
+        env.storage().instance().set(&DataKey::TokenDebt(token.clone()), &total);
+        Ok(())
+    }
+
+    // Bayar utang gaji dari saldo kontrak: lunas semua kalau cukup, selain itu pro-rata
+    fn settle_wage_debts(env: &Env, token: &Address) -> Result<i128, Error> {
+        let total_debt = Self::read_total_debt(env, token);
+        if total_debt <= 0 {
+            return Ok(0);
+        }
+        let token_client = token::Client::new(env, token);
+        // Dana terbatas funder tidak ikut dipakai melunasi utang gaji
+        let balance = token_client
+            .balance(&env.current_contract_address())
+            .checked_sub(Self::read_restricted_total(env, token))
+            .ok_or(Error::Overflow)?;
+        if balance <= 0 {
+            return Ok(0);
+        }
+
+        let list_key = DataKey::TokenDebtList(token.clone());
+        let debtors: Vec<Address> = env.storage().persistent().get(&list_key).unwrap_or(Vec::new(env));
+        let mut remaining_debtors = Vec::new(env);
+        let mut paid_total: i128 = 0;
+        for addr in debtors.iter() {
+            let key = DataKey::WageDebt(addr.clone());
+            let debt: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+            if debt <= 0 {
+                continue;
+            }
+            let pay = if balance >= total_debt {
+                debt
+            } else {
+                debt.checked_mul(balance).ok_or(Error::Overflow)? / total_debt
+            };
+            let left = debt.checked_sub(pay).ok_or(Error::Overflow)?;
+            if left > 0 {
+                env.storage().persistent().set(&key, &left);
+                remaining_debtors.push_back(addr.clone());
+            } else {
+                env.storage().persistent().remove(&key);
+            }
+            if pay > 0 {
+                token_client.transfer(&env.current_contract_address(), &addr, &pay);
+                env.events().publish((symbol_short!("debt_paid"), &addr), pay);
+                paid_total = paid_total.checked_add(pay).ok_or(Error::Overflow)?;
+            }
+        }
+
+        env.storage().persistent().set(&list_key, &remaining_debtors);
+        env.storage().instance().set(
+            &DataKey::TokenDebt(token.clone()),
+            &total_debt.checked_sub(paid_total).ok_or(Error::Overflow)?,
+        );
+        Self::adjust_totals(env, &DataKey::TokenTotals(token.clone()), Self::accrual_now(env), 0, -paid_total)?;
+        Ok(paid_total)
+    }
+
+    fn read_oracle_config(env: &Env) -> Result<OracleConfig, Error> {
+        env.storage().instance().get(&DataKey::Oracle).ok_or(Error::OracleNotConfigured)
+    }
+
+    fn oracle_price(env: &Env, config: &OracleConfig, oracle: &PriceOracleClient, asset: Asset) -> Result<i128, Error> {
+        // Aset basis oracle tidak punya harga sendiri; nilainya 1 dalam desimal oracle
+        if asset == oracle.base() {
+            return 10_i128.checked_pow(oracle.decimals()).ok_or(Error::Overflow);
+        }
+        let data = oracle.lastprice(&asset).ok_or(Error::OraclePriceUnavailable)?;
+        if data.price <= 0 {
+            return Err(Error::OraclePriceUnavailable);
+        }
+        if env.ledger().timestamp() > data.timestamp.saturating_add(config.max_age_seconds) {
+            return Err(Error::OraclePriceStale);
+        }
+        Ok(data.price)
+    }
+
+    // Berapa unit `token` untuk satu unit `currency`, diskalakan RATE_SCALE. Kurs yang lolos
+    // disimpan sebagai acuan cek deviasi berikutnya.
+    fn fx_rate(env: &Env, currency: &Symbol, token: &Address) -> Result<i128, Error> {
+        let config = Self::read_oracle_config(env)?;
+        let oracle = PriceOracleClient::new(env, &config.oracle);
+        let currency_price = Self::oracle_price(env, &config, &oracle, Asset::Other(currency.clone()))?;
+        let token_price = Self::oracle_price(env, &config, &oracle, Asset::Stellar(token.clone()))?;
+        let rate = currency_price
+            .checked_mul(RATE_SCALE)
+            .ok_or(Error::Overflow)?
+            / token_price;
+        if rate <= 0 {
+            return Err(Error::OraclePriceUnavailable);
+        }
+
+        let key = DataKey::FxRate(currency.clone(), token.clone());
+        // max_deviation_bps 0 mematikan cek deviasi
+        let last = env.storage().instance().get::<_, i128>(&key).filter(|_| config.max_deviation_bps > 0);
+        if let Some(last) = last {
+            let diff = rate.checked_sub(last).ok_or(Error::Overflow)?.abs();
+            let limit = last.checked_mul(i128::from(config.max_deviation_bps)).ok_or(Error::Overflow)?;
+            if diff.checked_mul(10_000).ok_or(Error::Overflow)? > limit {
+                return Err(Error::OraclePriceDeviation);
+            }
+        }
+        env.storage().instance().set(&key, &rate);
+        Ok(rate)
+    }
+
+    // Kurs oracle, atau kurs terakhir yang lolos cek kalau oracle sedang tidak bisa dipakai
+    fn fx_rate_or_last(env: &Env, currency: &Symbol, token: &Address) -> Result<i128, Error> {
+        Self::fx_rate(env, currency, token).or_else(|err| {
+            env.storage()
+                .instance()
+                .get(&DataKey::FxRate(currency.clone(), token.clone()))
+                .ok_or(err)
+        })
+    }
+
+    // Jumlah token yang dibayar untuk `amount` dalam denominasi gaji employee (dibulatkan ke bawah)
+    fn payout_amount(env: &Env, employee: &Employee, amount: i128) -> Result<i128, Error> {
+        match &employee.wage_currency {
+            None => Ok(amount),
+            Some(currency) => {
+                let rate = Self::fx_rate(env, currency, &employee.token)?;
+                Ok(amount.checked_mul(rate).ok_or(Error::Overflow)? / RATE_SCALE)
+            }
+        }
+    }
+
+    fn read_restricted_total(env: &Env, token: &Address) -> i128 {
+        env.storage().instance().get(&DataKey::RestrictedTotal(token.clone())).unwrap_or(0)
+    }
+
+    fn read_restricted_funders(env: &Env, token: &Address) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RestrictedFunders(token.clone()))
+            .unwrap_or(Vec::new(env))
+    }
+
+    fn read_restricted_tokens(env: &Env) -> Vec<Address> {
+        env.storage().instance().get(&DataKey::RestrictedTokens).unwrap_or(Vec::new(env))
+    }
+
+    fn write_restricted_funders(env: &Env, token: &Address, funders: &Vec<Address>) {
+        env.storage().persistent().set(&DataKey::RestrictedFunders(token.clone()), funders);
+        let mut tokens = Self::read_restricted_tokens(env);
+        match tokens.first_index_of(token) {
+            Some(idx) if funders.is_empty() => {
+                tokens.remove(idx);
+            }
+            None if !funders.is_empty() => tokens.push_back(token.clone()),
+            _ => return,
+        }
+        env.storage().instance().set(&DataKey::RestrictedTokens, &tokens);
+    }
+
+    fn funding_allows(env: &Env, funder: &Address, payee: &Address) -> bool {
+        let restriction: FundingRestriction = match env.storage().persistent().get(&DataKey::FundingRestriction(funder.clone())) {
+            Some(r) => r,
+            None => return true,
+        };
+        if restriction.employees.contains(payee) {
+            return true;
+        }
+        let centre: Option<Symbol> = env.storage().persistent().get(&DataKey::CostCentre(payee.clone()));
+        centre.is_some_and(|centre| restriction.cost_centres.contains(&centre))
+    }
+
+    // Dana terbatas di `token` yang tidak boleh dipakai membayar `payee`
+    fn restricted_excluding(env: &Env, token: &Address, payee: &Address) -> i128 {
+        let mut blocked: i128 = 0;
+        for funder in Self::read_restricted_funders(env, token).iter() {
+            if !Self::funding_allows(env, &funder, payee) {
+                let balance: i128 = env
+                    .storage()
+                    .persistent()
+                    .get(&DataKey::RestrictedBalance(funder, token.clone()))
+                    .unwrap_or(0);
+                blocked = blocked.saturating_add(balance);
+            }
+        }
+        blocked
+    }
+
+    // Kurangi saldo terbatas funder yang memang boleh membayar `payee`, supaya dana mereka
+    // terpakai sesuai peruntukan lebih dulu; setiap pemakaian dicatat untuk rekonsiliasi
+    fn consume_restricted(env: &Env, token: &Address, payee: &Address, amount: i128) -> Result<(), Error> {
+        let mut remaining = amount;
+        let mut total = Self::read_restricted_total(env, token);
+        let mut funders = Vec::new(env);
+        for funder in Self::read_restricted_funders(env, token).iter() {
+            let key = DataKey::RestrictedBalance(funder.clone(), token.clone());
+            let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+            let used = if remaining > 0 && Self::funding_allows(env, &funder, payee) { balance.min(remaining) } else { 0 };
+            if used > 0 {
+                remaining = remaining.checked_sub(used).ok_or(Error::Overflow)?;
+                total = total.checked_sub(used).ok_or(Error::Overflow)?;
+                env.events().publish((symbol_short!("fund_use"), &funder, payee), (token.clone(), used));
+            }
+            let left = balance.checked_sub(used).ok_or(Error::Overflow)?;
+            if left > 0 {
+                env.storage().persistent().set(&key, &left);
+                funders.push_back(funder);
+            } else {
+                env.storage().persistent().remove(&key);
+            }
+        }
+        Self::write_restricted_funders(env, token, &funders);
+        env.storage().instance().set(&DataKey::RestrictedTotal(token.clone()), &total.max(0));
+        Ok(())
+    }
+
+    // Pastikan saldo `token` yang boleh dipakai untuk `payee` cukup untuk `amount`. Kalau kurang
+    // dan auto top-up aktif, tarik kekurangannya saja dari allowance employer lewat `transfer_from`.
+    fn ensure_liquidity(env: &Env, token: &Address, payee: &Address, amount: i128) -> Result<(), Error> {
+        let token_client = token::Client::new(env, token);
+        let contract = env.current_contract_address();
+        let usable = token_client
+            .balance(&contract)
+            .checked_sub(Self::restricted_excluding(env, token, payee))
+            .ok_or(Error::Overflow)?;
+        if amount <= usable {
+            return Ok(());
+        }
+        let key = DataKey::AutoTopUp(token.clone());
+        let mut topup: AutoTopUp = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::InsufficientContractBalance)?;
+        let now = env.ledger().timestamp();
+        if now >= topup.window_start.saturating_add(topup.period_seconds) {
+            topup.window_start = now;
+            topup.pulled_in_window = 0;
+        }
+        let shortfall = amount.checked_sub(usable).ok_or(Error::Overflow)?;
+        let pulled = topup.pulled_in_window.checked_add(shortfall).ok_or(Error::Overflow)?;
+        let employer = Self::read_employer(env)?;
+        if pulled > topup.max_per_period
+            || token_client.allowance(&employer, &contract) < shortfall
+            || token_client.balance(&employer) < shortfall
+        {
+            return Err(Error::InsufficientContractBalance);
+        }
+
+        token_client.transfer_from(&contract, &employer, &contract, &shortfall);
+        topup.pulled_in_window = pulled;
+        env.storage().instance().set(&key, &topup);
+        env.events().publish((Symbol::new(env, "auto_topup"), token), (shortfall, pulled));
+        Ok(())
+    }
+
+    // Lepaskan sisa dana terbatas `funder` di `token` dari pembukuan; tokennya tetap di kontrak
+    fn release_restricted(env: &Env, token: &Address, funder: &Address) -> Result<i128, Error> {
+        let key = DataKey::RestrictedBalance(funder.clone(), token.clone());
+        let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage().persistent().remove(&key);
+        let mut funders = Self::read_restricted_funders(env, token);
+        if let Some(idx) = funders.first_index_of(funder) {
+            funders.remove(idx);
+            Self::write_restricted_funders(env, token, &funders);
+        }
+        if balance > 0 {
+            let total = Self::read_restricted_total(env, token).checked_sub(balance).ok_or(Error::Overflow)?;
+            env.storage().instance().set(&DataKey::RestrictedTotal(token.clone()), &total.max(0));
+        }
+        Ok(balance)
+    }
+
+    // Transfer gaji ke employee lewat jalur normal: cek likuiditas (termasuk batasan dana funder
+    // dan auto top-up), catat pemakaian dana terbatas, lalu kirim
+    fn pay_out(env: &Env, token: &Address, payee: &Address, amount: i128) -> Result<(), Error> {
+        Self::ensure_liquidity(env, token, payee, amount)?;
+        Self::consume_restricted(env, token, payee, amount)?;
+        token::Client::new(env, token).transfer(&env.current_contract_address(), payee, &amount);
+        Ok(())
+    }
+
+    // Kembalikan semua sisa dana terbatas ke funder-nya, termasuk di token yang sudah keluar
+    // dari allowlist; dipakai saat close-out dan wind-down
+    fn refund_restricted(env: &Env) -> Result<(), Error> {
+        for token in Self::read_restricted_tokens(env).iter() {
+            for funder in Self::read_restricted_funders(env, &token).iter() {
+                let refund = Self::release_restricted(env, &token, &funder)?
+                    .min(Self::contract_token_balance(env, &token));
+                if refund > 0 {
+                    token::Client::new(env, &token).transfer(&env.current_contract_address(), &funder, &refund);
+                    env.events().publish((symbol_short!("fund_rfnd"), &funder, &token), refund);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Dana terbatas dari funder tidak pernah dihitung sebagai surplus employer
+    fn free_surplus(env: &Env, token: &Address, contract_balance: i128) -> Result<i128, Error> {
+        let liabilities = Self::total_liabilities(env, token, Self::accrual_now(env))?;
+        Self::surplus_over(env, token, contract_balance, liabilities)
+    }
+
+    fn surplus_over(env: &Env, token: &Address, contract_balance: i128, liabilities: i128) -> Result<i128, Error> {
+        let reserved = liabilities
+            .checked_add(Self::read_restricted_total(env, token))
+            .ok_or(Error::Overflow)?;
+        let surplus = contract_balance.checked_sub(reserved).ok_or(Error::Overflow)?;
+        Ok(surplus.max(0))
+    }
+
+    // Tulis record Employee baru; dipakai add_employee dan accept_offer
+    fn hire_employee(
+        env: &Env,
+        employee_address: &Address,
+        wage_rate: i128,
+        wage_period: WagePeriod,
+        start_at: Option<u64>,
+        end_at: Option<u64>,
+        token: Address,
+    ) -> Result<(), Error> {
+        if wage_rate <= 0 {
+            return Err(Error::InvalidWageRate);
+        }
+        Self::validate_wage_period(wage_period)?;
+        Self::require_allowed_token(env, &token)?;
+        let key = DataKey::Employee(employee_address.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(Error::EmployeeAlreadyExists);
+        }
+        let now = Self::accrual_now(env);
+        let start_at = start_at.unwrap_or(now);
+        if start_at < now || end_at.is_some_and(|end| end <= start_at) {
+            return Err(Error::InvalidEmploymentTerm);
+        }
+        let data = Employee {
+            wage_rate,
+            last_accrual_timestamp: start_at,
+            wage_period,
+            accrued_balance: 0,
+            accrual_remainder: 0,
+            active: true,
+            suspended_at: 0,
+            total_suspended_seconds: 0,
+            leave_windows: Vec::new(env),
+            start_at,
+            end_at,
+            ended: false,
+            scheduled_rates: Vec::new(env),
+            token,
+            wage_currency: None,
+        };
+        env.storage().persistent().set(&key, &data);
+        Self::adjust_totals(env, &Self::totals_key(&data), now, Self::employee_rate_per_second_scaled(&data)?, 0)?;
+        if start_at > now {
+            env.storage().persistent().set(&DataKey::PreStart(employee_address.clone()), &now);
+        }
+        env.storage().persistent().set(
+            &DataKey::LeaveBalance(employee_address.clone()),
+            &Self::read_leave_balance(env, employee_address, &data, start_at),
+        );
+
+        Self::push_rate_history(env, employee_address, RateChange { effective_at: start_at, wage_rate });
+
+        let mut list: Vec<Address> = env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(env));
+        list.push_back(employee_address.clone());
+        env.storage().persistent().set(&DataKey::EmployeeList, &list);
+
+        env.events().publish((symbol_short!("hire"), employee_address), (data.wage_rate, start_at, end_at));
+        Ok(())
+    }
+
+    fn apply_withdrawal(employee_data: &mut Employee, amount: i128, now: u64) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        // Checkpoint dulu; sisa pecahan tetap dibawa sehingga penarikan parsial tidak
+        // pernah menambah atau menghilangkan nilai
+        let available = Self::settle_accrual(employee_data, now)?;
+        if amount > available {
+            return Err(Error::WithdrawalExceedsAccrued);
+        }
+        employee_data.accrued_balance = available.checked_sub(amount).ok_or(Error::Overflow)?;
+        Ok(())
+    }
+}
+
+#[contractimpl]
+impl FairWage {
+    pub fn initialize(env: Env, employer: Address, token_address: Address) -> Result<(), Error> {
+        employer.require_auth();
+        if env.storage().instance().has(&DataKey::Employer) {
+            return Err(Error::AlreadyInitialized);
+        }
+        env.storage().instance().set(&DataKey::Employer, &employer);
+        env.storage().instance().set(&DataKey::Token, &token_address);
+        env.storage().instance().set(&DataKey::AllowedTokens, &Vec::from_array(&env, [token_address.clone()]));
+        env.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        env.storage()
+            .instance()
+            .set(&DataKey::TokenTotals(token_address.clone()), &Self::read_totals(&env, &DataKey::TokenTotals(token_address.clone())));
+        env.storage().instance().set(&DataKey::LastEmployerActivity, &env.ledger().timestamp());
+        env.storage().persistent().set(&DataKey::EmployeeList, &Vec::<Address>::new(&env));
+        Ok(())
+    }
+
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+        env.events().publish((symbol_short!("upgrade"), &employer), new_wasm_hash);
+        Ok(())
+    }
+
+    // Dipanggil setelah `upgrade`: tulis ulang semua record Employee ke layout terbaru
+    pub fn migrate(env: Env) -> Result<u32, Error> {
+        let _employer = Self::require_employer_auth(&env)?;
+        let from_version = Self::read_schema_version(&env);
+        if from_version >= SCHEMA_VERSION && env.storage().instance().has(&DataKey::SchemaVersion) {
+            return Err(Error::AlreadyMigrated);
+        }
+
+        let now = Self::accrual_now(&env);
+        let list: Vec<Address> = env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(&env));
+        let mut migrated: u32 = 0;
+        for addr in list.iter() {
+            let key = DataKey::Employee(addr.clone());
+            let raw: Map<Symbol, Val> = match env.storage().persistent().get(&key) {
+                Some(v) => v,
+                None => continue,
+            };
+            let data = Self::migrate_employee_record(&env, &raw, now)?;
+            Self::write_employee(&env, &addr, &data);
+            // Record lama belum punya riwayat rate; mulai dari rate saat ini supaya `get_rate_segments` tidak kosong
+            if !env.storage().persistent().has(&DataKey::RateHistory(addr.clone())) {
+                Self::push_rate_history(&env, &addr, RateChange { effective_at: data.start_at, wage_rate: data.wage_rate });
+            }
+            migrated = migrated.checked_add(1).ok_or(Error::Overflow)?;
+        }
+
+        // Sebelum v9 hanya ada satu token: pindahkan state global ke key per token utama
+        if from_version < 9 {
+            let token = Self::read_token(&env)?;
+            let mut allowed = Self::read_allowed_tokens(&env);
+            if !allowed.contains(&token) {
+                allowed.push_back(token.clone());
+                env.storage().instance().set(&DataKey::AllowedTokens, &allowed);
+            }
+            env.storage()
+                .instance()
+                .set(&DataKey::TokenTotals(token.clone()), &Self::recompute_totals(&env, &DataKey::TokenTotals(token.clone()), now)?);
+            env.storage().instance().remove(&DataKey::Totals);
+
+            let total_debt: i128 = env.storage().instance().get(&DataKey::TotalDebt).unwrap_or(0);
+            env.storage().instance().set(&DataKey::TokenDebt(token.clone()), &total_debt);
+            env.storage().instance().remove(&DataKey::TotalDebt);
+            if let Some(debtors) = env.storage().persistent().get::<_, Vec<Address>>(&DataKey::DebtList) {
+                env.storage().persistent().set(&DataKey::TokenDebtList(token.clone()), &debtors);
+                env.storage().persistent().remove(&DataKey::DebtList);
+            }
+            if let Some(since) = env.storage().instance().get::<_, u64>(&DataKey::ShortfallSince) {
+                env.storage().instance().set(&DataKey::TokenShortfall(token.clone()), &since);
+                env.storage().instance().remove(&DataKey::ShortfallSince);
+            }
+        }
+
+        env.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        env.events().publish((symbol_short!("migrate"), from_version), (SCHEMA_VERSION, migrated));
+        Ok(migrated)
+    }
+
+    pub fn get_schema_version(env: Env) -> u32 {
+        Self::read_schema_version(&env)
+    }
+
+    // Buang drift pembulatan di semua agregat satu token dengan menghitung ulang dari semua record.
+    // Yang dikembalikan agregat untuk gaji yang memang dalam token itu (bukan fiat).
+    pub fn resync_totals(env: Env, token: Address) -> Result<PayrollTotals, Error> {
+        let _employer = Self::require_employer_auth(&env)?;
+        let now = Self::accrual_now(&env);
+        for key in Self::totals_keys(&env, &token).iter() {
+            let totals = Self::recompute_totals(&env, &key, now)?;
+            env.storage().instance().set(&key, &totals);
+        }
+        Ok(Self::read_totals(&env, &DataKey::TokenTotals(token)))
+    }
+
+    // Langkah 1 rotasi employer: owner saat ini menunjuk calon employer baru
+    pub fn propose_employer(env: Env, new_employer: Address) -> Result<(), Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        env.storage().instance().set(&DataKey::PendingEmployer, &new_employer);
+        env.events().publish((symbol_short!("emp_prop"), &employer), new_employer);
+        Ok(())
+    }
+
+    // Langkah 2: calon employer harus tanda tangan sendiri untuk menerima
+    pub fn accept_employer(env: Env, new_employer: Address) -> Result<(), Error> {
+        new_employer.require_auth();
+        let pending: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingEmployer)
+            .ok_or(Error::NoPendingEmployer)?;
+        if pending != new_employer {
+            return Err(Error::NotAuthorized);
+        }
+        let old_employer = Self::read_employer(&env)?;
+        env.storage().instance().set(&DataKey::Employer, &new_employer);
+        env.storage().instance().remove(&DataKey::PendingEmployer);
+        env.events().publish(
+            (Symbol::new(&env, "employer_changed"), &old_employer),
+            new_employer,
+        );
+        Ok(())
+    }
+
+    pub fn cancel_employer_transfer(env: Env) -> Result<(), Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        if !env.storage().instance().has(&DataKey::PendingEmployer) {
+            return Err(Error::NoPendingEmployer);
+        }
+        env.storage().instance().remove(&DataKey::PendingEmployer);
+        env.events().publish((symbol_short!("emp_cncl"), &employer), ());
+        Ok(())
+    }
+
+    pub fn get_employer(env: Env) -> Result<Address, Error> {
+        Self::read_employer(&env)
+    }
+
+    pub fn get_pending_employer(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PendingEmployer)
+    }
+
+    pub fn grant_role(env: Env, account: Address, role: Role) -> Result<(), Error> {
+        let _employer = Self::require_employer_auth(&env)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Role(account.clone(), role), &true);
+        env.events().publish((symbol_short!("role_add"), &account), role);
+        Ok(())
+    }
+
+    pub fn revoke_role(env: Env, account: Address, role: Role) -> Result<(), Error> {
+        let _employer = Self::require_employer_auth(&env)?;
+        let key = DataKey::Role(account.clone(), role);
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::RoleNotGranted);
+        }
+        env.storage().persistent().remove(&key);
+        env.events().publish((symbol_short!("role_del"), &account), role);
+        Ok(())
+    }
+
+    pub fn has_role(env: Env, account: Address, role: Role) -> bool {
+        Self::has_role_internal(&env, &account, role)
+    }
+
+    // `payout_token` None berarti dibayar dengan token utama kontrak
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_employee(
+        env: Env,
+        caller: Address,
+        employee_address: Address,
+        wage_rate: i128,
+        wage_period: WagePeriod,
+        start_at: Option<u64>,
+        end_at: Option<u64>,
+        payout_token: Option<Address>,
+    ) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::require_open(&env)?;
+        // Employee ikut menandatangani transaksi yang sama sebagai persetujuan
+        employee_address.require_auth();
+        let token = match payout_token {
+            Some(token) => token,
+            None => Self::read_token(&env)?,
+        };
+        Self::hire_employee(&env, &employee_address, wage_rate, wage_period, start_at, end_at, token)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn offer_employment(
+        env: Env,
+        caller: Address,
+        employee_address: Address,
+        wage_rate: i128,
+        wage_period: WagePeriod,
+        start_at: Option<u64>,
+        end_at: Option<u64>,
+        terms_hash: Option<BytesN<32>>,
+    ) -> Result<EmploymentOffer, Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::require_open(&env)?;
+        if wage_rate <= 0 {
+            return Err(Error::InvalidWageRate);
+        }
+        Self::validate_wage_period(wage_period)?;
+        if env.storage().persistent().has(&DataKey::Employee(employee_address.clone())) {
+            return Err(Error::EmployeeAlreadyExists);
+        }
+        let now = Self::accrual_now(&env);
+        if start_at.is_some_and(|start| start < now)
+            || end_at.is_some_and(|end| end <= start_at.unwrap_or(now))
+        {
+            return Err(Error::InvalidEmploymentTerm);
+        }
+
+        let offer = EmploymentOffer {
+            wage_rate,
+            wage_period,
+            start_at,
+            end_at,
+            terms_hash,
+            offered_at: now,
+            expires_at: now.saturating_add(OFFER_TTL_SECONDS),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Offer(employee_address.clone()), &offer);
+        env.events().publish((symbol_short!("offer"), &employee_address), offer.clone());
+        Ok(offer)
+    }
+
+    // Employee menerima tawaran; accrual mulai dari start_at (atau sekarang kalau sudah lewat).
+    // Tawaran selalu dibayar dengan token utama; pindah token lewat `set_payout_token`.
+    pub fn accept_offer(env: Env, employee_address: Address) -> Result<(), Error> {
+        employee_address.require_auth();
+        Self::require_open(&env)?;
+        let key = DataKey::Offer(employee_address.clone());
+        let offer: EmploymentOffer = env.storage().persistent().get(&key).ok_or(Error::OfferNotFound)?;
+        let now = Self::accrual_now(&env);
+        if now > offer.expires_at {
+            return Err(Error::OfferExpired);
+        }
+        env.storage().persistent().remove(&key);
+        let start_at = offer.start_at.map(|start| start.max(now));
+        Self::hire_employee(
+            &env,
+            &employee_address,
+            offer.wage_rate,
+            offer.wage_period,
+            start_at,
+            offer.end_at,
+            Self::read_token(&env)?,
+        )?;
+        env.events().publish((symbol_short!("offer_acc"), &employee_address), offer.terms_hash);
+        Ok(())
+    }
+
+    pub fn withdraw_offer(env: Env, caller: Address, employee_address: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        let key = DataKey::Offer(employee_address.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::OfferNotFound);
+        }
+        env.storage().persistent().remove(&key);
+        env.events().publish((symbol_short!("offer_del"), &employee_address), ());
+        Ok(())
+    }
+
+    pub fn get_offer(env: Env, employee_address: Address) -> Option<EmploymentOffer> {
+        env.storage().persistent().get(&DataKey::Offer(employee_address))
+    }
+
+    pub fn update_wage_rate(env: Env, caller: Address, employee_address: Address, new_wage_rate: i128) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        if new_wage_rate <= 0 { return Err(Error::InvalidWageRate); }
+
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        let now = Self::accrual_now(&env);
+
+        // Kenaikan langsung berlaku; penurunan menunggu persetujuan employee
+        if new_wage_rate < Self::rate_at(&data, now) {
+            Self::propose_wage_terms(&env, &employee_address, new_wage_rate, data.wage_period, now);
+            return Ok(());
+        }
+        let period = data.wage_period;
+        Self::apply_wage_terms(&env, &employee_address, &mut data, new_wage_rate, period, now)?;
+        env.events().publish((symbol_short!("wage_set"), &employee_address), new_wage_rate);
+        Ok(())
+    }
+
+    // Ganti periode gaji selalu butuh persetujuan employee
+    pub fn update_wage_period(
+        env: Env,
+        caller: Address,
+        employee_address: Address,
+        new_wage_rate: i128,
+        new_wage_period: WagePeriod,
+    ) -> Result<WageProposal, Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        if new_wage_rate <= 0 { return Err(Error::InvalidWageRate); }
+        Self::validate_wage_period(new_wage_period)?;
+        Self::read_employee(&env, &employee_address)?;
+        let now = Self::accrual_now(&env);
+        Ok(Self::propose_wage_terms(&env, &employee_address, new_wage_rate, new_wage_period, now))
+    }
+
+    pub fn accept_wage_change(env: Env, employee_address: Address) -> Result<(), Error> {
+        employee_address.require_auth();
+        let key = DataKey::WageProposal(employee_address.clone());
+        let proposal: WageProposal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::NoPendingWageChange)?;
+        let now = Self::accrual_now(&env);
+        if now > proposal.expires_at {
+            return Err(Error::WageProposalExpired);
+        }
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        Self::apply_wage_terms(&env, &employee_address, &mut data, proposal.wage_rate, proposal.wage_period, now)?;
+        env.storage().persistent().remove(&key);
+        env.events().publish((symbol_short!("wage_acc"), &employee_address), proposal);
+        Ok(())
+    }
+
+    pub fn reject_wage_change(env: Env, employee_address: Address) -> Result<(), Error> {
+        employee_address.require_auth();
+        let key = DataKey::WageProposal(employee_address.clone());
+        let proposal: WageProposal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::NoPendingWageChange)?;
+        env.storage().persistent().remove(&key);
+        env.events().publish((symbol_short!("wage_rej"), &employee_address), proposal);
+        Ok(())
+    }
+
+    pub fn get_wage_proposal(env: Env, employee_address: Address) -> Option<WageProposal> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::WageProposal(employee_address))
+    }
+
+    // Antrekan perubahan rate yang berlaku di masa depan (kenaikan tahunan, promosi, dst.)
+    pub fn schedule_wage_rate(
+        env: Env,
+        caller: Address,
+        employee_address: Address,
+        new_wage_rate: i128,
+        effective_at: u64,
+    ) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        if new_wage_rate <= 0 { return Err(Error::InvalidWageRate); }
+
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        let now = Self::accrual_now(&env);
+        if effective_at <= now {
+            return Err(Error::InvalidRateChange);
+        }
+        // Penurunan tidak bisa dijadwalkan sepihak; pakai update_wage_rate supaya employee menyetujui
+        if new_wage_rate < Self::rate_at(&data, effective_at) {
+            return Err(Error::InvalidRateChange);
+        }
+        // Jadwal yang sudah ada setelahnya juga tidak boleh berubah jadi penurunan
+        if data
+            .scheduled_rates
+            .iter()
+            .any(|change| change.effective_at > effective_at && change.wage_rate < new_wage_rate)
+        {
+            return Err(Error::InvalidRateChange);
+        }
+        Self::settle_accrual(&mut data, now)?;
+        Self::settle_overcount(&env, &employee_address, &data, now)?;
+        let old_rate = Self::employee_rate_per_second_scaled(&data)?;
+        Self::fold_applied_rates(&env, &mut data);
+
+        // Sisipkan urut menurut effective_at; jadwal di waktu yang sama diganti
+        let mut schedule = Vec::new(&env);
+        let mut inserted = false;
+        for change in data.scheduled_rates.iter() {
+            if !inserted && effective_at <= change.effective_at {
+                schedule.push_back(RateChange { effective_at, wage_rate: new_wage_rate });
+                inserted = true;
+                if change.effective_at == effective_at {
+                    continue;
+                }
+            }
+            schedule.push_back(change);
+        }
+        if !inserted {
+            schedule.push_back(RateChange { effective_at, wage_rate: new_wage_rate });
+        }
+        if schedule.len() > MAX_SCHEDULED_RATES {
+            return Err(Error::InvalidRateChange);
+        }
+        data.scheduled_rates = schedule;
+        Self::write_employee(&env, &employee_address, &data);
+
+        if data.active {
+            let new_rate = Self::employee_rate_per_second_scaled(&data)?;
+            Self::adjust_totals(&env, &Self::totals_key(&data), now, new_rate.checked_sub(old_rate).ok_or(Error::Overflow)?, 0)?;
+        }
+        Self::push_rate_history(&env, &employee_address, RateChange { effective_at, wage_rate: new_wage_rate });
+        env.events().publish((symbol_short!("rate_schd"), &employee_address), (new_wage_rate, effective_at));
+        Ok(())
+    }
+
+    pub fn cancel_wage_rate_change(
+        env: Env,
+        caller: Address,
+        employee_address: Address,
+        effective_at: u64,
+    ) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        let now = Self::accrual_now(&env);
+        if effective_at <= now {
+            return Err(Error::RateChangeNotFound);
+        }
+        Self::settle_accrual(&mut data, now)?;
+        Self::settle_overcount(&env, &employee_address, &data, now)?;
+        let old_rate = Self::employee_rate_per_second_scaled(&data)?;
+
+        let before = data.scheduled_rates.len();
+        let mut schedule = Vec::new(&env);
+        for change in data.scheduled_rates.iter() {
+            if change.effective_at != effective_at {
+                schedule.push_back(change);
+            }
+        }
+        if schedule.len() == before {
+            return Err(Error::RateChangeNotFound);
+        }
+        data.scheduled_rates = schedule;
+        Self::write_employee(&env, &employee_address, &data);
+
+        if data.active {
+            let new_rate = Self::employee_rate_per_second_scaled(&data)?;
+            Self::adjust_totals(&env, &Self::totals_key(&data), now, new_rate.checked_sub(old_rate).ok_or(Error::Overflow)?, 0)?;
+        }
+
+        // Riwayat hanya menyimpan segmen yang benar-benar berlaku atau masih dijadwalkan
+        let history_key = DataKey::RateHistory(employee_address.clone());
+        let history: Vec<RateChange> = env.storage().persistent().get(&history_key).unwrap_or(Vec::new(&env));
+        let mut kept = Vec::new(&env);
+        for change in history.iter() {
+            if change.effective_at != effective_at {
+                kept.push_back(change);
+            }
+        }
+        env.storage().persistent().set(&history_key, &kept);
+
+        env.events().publish((symbol_short!("rate_cncl"), &employee_address), effective_at);
+        Ok(())
+    }
+
+    pub fn get_rate_segments(env: Env, employee_address: Address) -> Result<RateSegments, Error> {
+        Self::read_employee(&env, &employee_address)?;
+        let now = env.ledger().timestamp();
+        let history: Vec<RateChange> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::RateHistory(employee_address))
+            .unwrap_or(Vec::new(&env));
+        let mut segments = RateSegments {
+            past: Vec::new(&env),
+            upcoming: Vec::new(&env),
+        };
+        for change in history.iter() {
+            if change.effective_at <= now {
+                segments.past.push_back(change);
+            } else {
+                segments.upcoming.push_back(change);
+            }
+        }
+        Ok(segments)
+    }
+
+    pub fn freeze_employee(env: Env, caller: Address, employee_address: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::require_not_paused(&env)?;
+        let key = DataKey::Employee(employee_address.clone());
+        let mut data: Employee = env.storage().persistent().get(&key).ok_or(Error::EmployeeNotFound)?;
+        
+        // Hitung dan bayar semua accrued wages. Kontrak yang sudah lewat `end_at` ditandai selesai
+        // dulu supaya tidak mendapat pesangon.
+        let now = Self::accrual_now(&env);
+        Self::end_term_if_due(&env, &employee_address, &mut data, now)?;
+        Self::settle_accrual(&mut data, now)?;
+        Self::pay_severance(&env, &employee_address, &data, now)?;
+        let paid = Self::pay_final_balance(&env, &employee_address, &mut data)?;
+        Self::settle_overcount(&env, &employee_address, &data, now)?;
+        
+        let rate_delta = if data.active { -Self::employee_rate_per_second_scaled(&data)? } else { 0 };
+        Self::adjust_totals(&env, &Self::totals_key(&data), now, rate_delta, -paid)?;
+
+        // Set employee sebagai non-aktif; accrual berhenti tepat di titik freeze
+        if data.active {
+            data.suspended_at = now;
+        }
+        data.active = false;
+        env.storage().persistent().set(&key, &data);
+        
+        env.events().publish((symbol_short!("freeze"), &employee_address), ());
+        Ok(())
+    }
+
+    pub fn activate_employee(env: Env, caller: Address, employee_address: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::require_open(&env)?;
+        let key = DataKey::Employee(employee_address.clone());
+        let mut data: Employee = env.storage().persistent().get(&key).ok_or(Error::EmployeeNotFound)?;
+        if data.ended || data.end_at.is_some_and(|end| Self::accrual_now(&env) >= end) {
+            return Err(Error::EmploymentEnded);
+        }
+        
+        let mut suspended_for: u64 = 0;
+        if !data.active {
+            let now = Self::accrual_now(&env);
+            Self::settle_overcount(&env, &employee_address, &data, now)?;
+            let rate = Self::employee_rate_per_second_scaled(&data)?;
+            Self::adjust_totals(&env, &Self::totals_key(&data), now, rate, 0)?;
+
+            // Masa freeze tidak pernah di-accrue: accrual dimulai lagi dari waktu aktivasi, atau
+            // dari `start_at` kalau employee belum mulai. Waktu sebelum `start_at` bukan masa suspend.
+            let resume_at = now.max(data.start_at);
+            // Saldo cuti di-checkpoint di titik freeze, lalu mulai bertambah lagi dari `resume_at`
+            let mut leave = Self::read_leave_balance(&env, &employee_address, &data, now);
+            leave.accrued_until = leave.accrued_until.max(resume_at);
+            env.storage().persistent().set(&DataKey::LeaveBalance(employee_address.clone()), &leave);
+            suspended_for = resume_at.saturating_sub(data.suspended_at.max(data.start_at));
+            data.total_suspended_seconds = data
+                .total_suspended_seconds
+                .checked_add(suspended_for)
+                .ok_or(Error::Overflow)?;
+            data.accrual_remainder = Self::rescale_remainder(
+                data.accrual_remainder,
+                Self::period_segment(data.wage_period, data.last_accrual_timestamp).1,
+                Self::period_segment(data.wage_period, resume_at).1,
+            )?;
+            data.last_accrual_timestamp = resume_at;
+            data.suspended_at = 0;
+        }
+
+        // Set employee sebagai aktif
+        data.active = true;
+        env.storage().persistent().set(&key, &data);
+        
+        env.events().publish((symbol_short!("activate"), &employee_address), suspended_for);
+        Ok(())
+    }
+
+    pub fn remove_employee(env: Env, caller: Address, employee_address: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::require_not_paused(&env)?;
+        let key = DataKey::Employee(employee_address.clone());
+        let mut data: Employee = env.storage().persistent().get(&key).ok_or(Error::EmployeeNotFound)?;
+        
+        // Hanya bisa remove employee yang sudah di-freeze (non-aktif)
+        if data.active { return Err(Error::CannotRemoveActiveEmployee); }
+
+        // Sisa gaji (mis. kontrak yang selesai tapi belum di-withdraw) dan pesangon yang belum
+        // dibayar saat freeze ikut dilunasi di sini
+        let now = Self::accrual_now(&env);
+        let outstanding = data.accrued_balance;
+        Self::pay_severance(&env, &employee_address, &data, now)?;
+        Self::pay_final_balance(&env, &employee_address, &mut data)?;
+        
+        env.storage().persistent().remove(&key);
+        env.storage().persistent().remove(&DataKey::Resignation(employee_address.clone()));
+        env.storage().persistent().remove(&DataKey::Severance(employee_address.clone()));
+        env.storage().persistent().remove(&DataKey::SeverancePaid(employee_address.clone()));
+        // Data per employee lain juga dibuang supaya rehire alamat yang sama mulai dari nol
+        env.storage().persistent().remove(&DataKey::LeaveBalance(employee_address.clone()));
+        env.storage().persistent().remove(&DataKey::RateHistory(employee_address.clone()));
+        env.storage().persistent().remove(&DataKey::WageProposal(employee_address.clone()));
+        env.storage().persistent().remove(&DataKey::CostCentre(employee_address.clone()));
+        env.storage().persistent().remove(&DataKey::PreStart(employee_address.clone()));
+        env.storage().persistent().remove(&DataKey::UnpaidLeaveSettled(employee_address.clone()));
+        Self::adjust_totals(&env, &Self::totals_key(&data), now, 0, -outstanding)?;
+
+        if let Some(list) = env.storage().persistent().get::<_, Vec<Address>>(&DataKey::EmployeeList) {
+            let mut new_list = Vec::new(&env);
+            for addr in list.iter() {
+                if addr != employee_address {
+                    new_list.push_back(addr.clone());
+                }
+            }
+            env.storage().persistent().set(&DataKey::EmployeeList, &new_list);
+        }
+
+        env.events().publish((symbol_short!("fire"), &employee_address), ());
+        Ok(())
+    }
+
+    pub fn set_leave_policy(env: Env, caller: Address, policy: LeavePolicy) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        env.storage().instance().set(&DataKey::LeavePolicy, &policy);
+        env.events().publish((symbol_short!("leave_pol"), &caller), policy);
+        Ok(())
+    }
+
+    // Jadwalkan cuti ke depan. Cuti berbayar memotong saldo sesuai jenisnya dan tetap di-accrue;
+    // cuti unpaid menghentikan accrual selama window-nya. Rate di agregat tidak berubah, jadi
+    // kelebihan selama cuti unpaid dikoreksi lewat `settle_unpaid_leave` setiap employee disentuh.
+    pub fn schedule_leave(
+        env: Env,
+        caller: Address,
+        employee_address: Address,
+        leave_type: LeaveType,
+        start: u64,
+        end: u64,
+    ) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        let now = Self::accrual_now(&env);
+        if end <= start || start < now || start < data.last_accrual_timestamp {
+            return Err(Error::InvalidLeaveWindow);
+        }
+        // Koreksi dulu cuti unpaid yang sudah berjalan sebelum window lama dibuang di bawah
+        Self::settle_overcount(&env, &employee_address, &data, now)?;
+
+        // Buang window yang sudah lewat, lalu tolak yang tumpang tindih
+        let mut windows = Vec::new(&env);
+        for w in data.leave_windows.iter() {
+            if w.end > data.last_accrual_timestamp {
+                if start < w.end && w.start < end {
+                    return Err(Error::InvalidLeaveWindow);
+                }
+                windows.push_back(w);
+            }
+        }
+        if windows.len() >= MAX_LEAVE_WINDOWS {
+            return Err(Error::InvalidLeaveWindow);
+        }
+
+        let duration = end - start;
+        let mut balance = Self::read_leave_balance(&env, &employee_address, &data, now);
+        match leave_type {
+            LeaveType::Vacation => {
+                balance.vacation_seconds = balance
+                    .vacation_seconds
+                    .checked_sub(duration)
+                    .ok_or(Error::InsufficientLeaveBalance)?;
+            }
+            LeaveType::Sick => {
+                balance.sick_seconds = balance
+                    .sick_seconds
+                    .checked_sub(duration)
+                    .ok_or(Error::InsufficientLeaveBalance)?;
+            }
+            LeaveType::Unpaid => {
+                balance.unpaid_seconds_taken = balance.unpaid_seconds_taken.saturating_add(duration);
+            }
+        }
+
+        windows.push_back(LeaveWindow { leave_type, start, end });
+        data.leave_windows = windows;
+        Self::write_employee(&env, &employee_address, &data);
+        if leave_type == LeaveType::Unpaid {
+            env.storage()
+                .persistent()
+                .set(&DataKey::UnpaidLeaveSettled(employee_address.clone()), &now);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::LeaveBalance(employee_address.clone()), &balance);
+        env.events().publish((symbol_short!("leave"), &employee_address), (leave_type, start, end));
+        Ok(())
+    }
+
+    // Batalkan cuti yang belum dimulai; saldo yang sudah dipotong dikembalikan
+    pub fn cancel_leave(env: Env, caller: Address, employee_address: Address, start: u64) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        let now = Self::accrual_now(&env);
+
+        let mut windows = Vec::new(&env);
+        let mut removed: Option<LeaveWindow> = None;
+        for w in data.leave_windows.iter() {
+            if removed.is_none() && w.start == start && w.start > now {
+                removed = Some(w);
+            } else {
+                windows.push_back(w);
+            }
+        }
+        let removed = removed.ok_or(Error::LeaveNotFound)?;
+
+        let duration = removed.end - removed.start;
+        let mut balance = Self::read_leave_balance(&env, &employee_address, &data, now);
+        match removed.leave_type {
+            LeaveType::Vacation => balance.vacation_seconds = balance.vacation_seconds.saturating_add(duration),
+            LeaveType::Sick => balance.sick_seconds = balance.sick_seconds.saturating_add(duration),
+            LeaveType::Unpaid => {
+                balance.unpaid_seconds_taken = balance.unpaid_seconds_taken.saturating_sub(duration)
+            }
+        }
+
+        data.leave_windows = windows;
+        // Window yang dibatalkan belum mulai, jadi belum pernah dikoreksi; cukup settle sisanya
+        Self::settle_overcount(&env, &employee_address, &data, now)?;
+        Self::write_employee(&env, &employee_address, &data);
+        env.storage()
+            .persistent()
+            .set(&DataKey::LeaveBalance(employee_address.clone()), &balance);
+        env.events().publish((symbol_short!("leave_del"), &employee_address), (removed.leave_type, start));
+        Ok(())
+    }
+
+    pub fn get_leave_balance(env: Env, employee_address: Address) -> Result<LeaveBalance, Error> {
+        let data = Self::read_employee(&env, &employee_address)?;
+        Ok(Self::read_leave_balance(&env, &employee_address, &data, Self::accrual_now(&env)))
+    }
+
+    pub fn get_leave_policy(env: Env) -> LeavePolicy {
+        Self::read_leave_policy(&env)
+    }
+
+    // Siapa pun boleh menandai kontrak fixed-term yang sudah lewat `end_at` sebagai selesai
+    pub fn end_fixed_term(env: Env, employee_address: Address) -> Result<(), Error> {
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        if !Self::end_term_if_due(&env, &employee_address, &mut data, Self::accrual_now(&env))? {
+            return Err(Error::InvalidEmploymentTerm);
+        }
+        Self::write_employee(&env, &employee_address, &data);
+        Ok(())
+    }
+
+    pub fn set_severance_policy(env: Env, caller: Address, policy: SeverancePolicy) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::validate_severance_policy(&policy)?;
+        env.storage().instance().set(&DataKey::SeverancePolicy, &policy);
+        env.events().publish((symbol_short!("sev_pol"), &caller), policy);
+        Ok(())
+    }
+
+    // `None` menghapus override sehingga employee kembali ke kebijakan kontrak
+    pub fn set_employee_severance(
+        env: Env,
+        caller: Address,
+        employee_address: Address,
+        policy: Option<SeverancePolicy>,
+    ) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::read_employee(&env, &employee_address)?;
+        let key = DataKey::Severance(employee_address.clone());
+        match &policy {
+            Some(policy) => {
+                Self::validate_severance_policy(policy)?;
+                env.storage().persistent().set(&key, policy);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+        env.events().publish((symbol_short!("sev_pol"), &caller, &employee_address), policy);
+        Ok(())
+    }
+
+    pub fn get_severance_policy(env: Env, employee_address: Address) -> SeverancePolicy {
+        Self::read_severance_policy(&env, &employee_address)
+    }
+
+    // Pesangon yang akan dibayar kalau employee di-freeze sekarang
+    pub fn get_severance_due(env: Env, employee_address: Address) -> Result<i128, Error> {
+        let data = Self::read_employee(&env, &employee_address)?;
+        Self::severance_due(&env, &employee_address, &data, Self::accrual_now(&env))
+    }
+
+    pub fn set_notice_period(env: Env, caller: Address, seconds: u64) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        env.storage().instance().set(&DataKey::NoticePeriod, &seconds);
+        env.events().publish((symbol_short!("notice"), &caller), seconds);
+        Ok(())
+    }
+
+    pub fn get_notice_period(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::NoticePeriod).unwrap_or(DEFAULT_NOTICE_PERIOD_SECONDS)
+    }
+
+    // Employee mengundurkan diri sendiri. Accrual tetap jalan selama masa notice lalu
+    // berhenti otomatis di akhir notice (lewat `end_at`), dan sisa saldo bisa di-withdraw.
+    pub fn resign(env: Env, employee_address: Address) -> Result<u64, Error> {
+        employee_address.require_auth();
+        Self::require_open(&env)?;
+        let resign_key = DataKey::Resignation(employee_address.clone());
+        if env.storage().persistent().has(&resign_key) {
+            return Err(Error::AlreadyResigned);
+        }
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        let now = Self::accrual_now(&env);
+        // Error membatalkan semua write, jadi kontrak yang baru lewat end_at cukup ditolak
+        if Self::end_term_if_due(&env, &employee_address, &mut data, now)? || data.ended {
+            return Err(Error::EmploymentEnded);
+        }
+
+        let notice_end = now.checked_add(Self::get_notice_period(env.clone())).ok_or(Error::Overflow)?;
+        // Kontrak fixed-term yang selesai lebih dulu tetap berakhir di tanggal aslinya
+        let end_at = data.end_at.map_or(notice_end, |end_at| end_at.min(notice_end));
+        data.end_at = Some(end_at);
+        Self::write_employee(&env, &employee_address, &data);
+        env.storage().persistent().set(&resign_key, &end_at);
+
+        env.events().publish((symbol_short!("resign"), &employee_address), end_at);
+        Ok(end_at)
+    }
+
+    // Employer melepas sisa masa notice: employment berakhir sekarang dan saldo dibayar
+    // langsung seperti `freeze_employee`
+    pub fn waive_notice(env: Env, caller: Address, employee_address: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::require_not_paused(&env)?;
+        let resign_key = DataKey::Resignation(employee_address.clone());
+        if !env.storage().persistent().has(&resign_key) {
+            return Err(Error::NotResigned);
+        }
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        let now = Self::accrual_now(&env);
+        if !data.ended {
+            data.end_at = Some(data.end_at.map_or(now, |end_at| end_at.min(now)));
+            Self::end_term_if_due(&env, &employee_address, &mut data, now)?;
+        }
+
+        let paid = Self::pay_final_balance(&env, &employee_address, &mut data)?;
+        Self::adjust_totals(&env, &Self::totals_key(&data), now, 0, -paid)?;
+        Self::write_employee(&env, &employee_address, &data);
+        env.storage().persistent().set(&resign_key, &data.end_at.unwrap_or(now));
+
+        env.events().publish((symbol_short!("waive"), &employee_address), paid);
+        Ok(())
+    }
+
+    pub fn get_resignation(env: Env, employee_address: Address) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::Resignation(employee_address))
+    }
+
+    pub fn list_employees(env: Env) -> Vec<Address> {
+        env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(&env))
+    }
+
+    pub fn get_live_accrued_balance(env: Env, employee_address: Address) -> Result<i128, Error> {
+        let data = Self::read_employee(&env, &employee_address)?;
+        let now = Self::accrual_now(&env);
+        let accrued = Self::calculate_accrued(&data, now)?;
+        data.accrued_balance.checked_add(accrued).ok_or(Error::Overflow)
+    }
+
+    pub fn allow_token(env: Env, caller: Address, token: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::Treasury)?;
+        let mut allowed = Self::read_allowed_tokens(&env);
+        if !allowed.contains(&token) {
+            allowed.push_back(token.clone());
+            env.storage().instance().set(&DataKey::AllowedTokens, &allowed);
+            env.storage()
+                .instance()
+                .set(&DataKey::TokenTotals(token.clone()), &Self::read_totals(&env, &DataKey::TokenTotals(token.clone())));
+        }
+        env.events().publish((symbol_short!("tok_allow"), &caller), token);
+        Ok(())
+    }
+
+    // Token hanya bisa dikeluarkan dari allowlist kalau tidak ada lagi kewajiban atau employee
+    // aktif di token itu. Sisa saldonya tetap bisa ditarik lewat `withdraw_surplus`.
+    pub fn disallow_token(env: Env, caller: Address, token: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::Treasury)?;
+        if token == Self::read_token(&env)? {
+            return Err(Error::TokenInUse);
+        }
+        if Self::read_total_debt(&env, &token) > 0 {
+            return Err(Error::TokenInUse);
+        }
+        // Employee yang sedang di-freeze dengan saldo nol tidak terlihat di agregat, tapi akan
+        // accrue lagi di token ini begitu di-activate
+        let list: Vec<Address> = env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(&env));
+        for addr in list.iter() {
+            if Self::read_employee(&env, &addr).is_ok_and(|emp| emp.token == token) {
+                return Err(Error::TokenInUse);
+            }
+        }
+        for key in Self::totals_keys(&env, &token).iter() {
+            let mut totals = Self::read_totals(&env, &key);
+            Self::roll_totals(&mut totals, Self::accrual_now(&env))?;
+            if totals.settled_accrued > 0 || totals.rate_per_second_scaled > 0 {
+                return Err(Error::TokenInUse);
+            }
+        }
+        let allowed = Self::read_allowed_tokens(&env);
+        let index = allowed.first_index_of(&token).ok_or(Error::TokenNotAllowed)?;
+        let mut allowed = allowed;
+        allowed.remove(index);
+        env.storage().instance().set(&DataKey::AllowedTokens, &allowed);
+        env.events().publish((symbol_short!("tok_deny"), &caller), token);
+        Ok(())
+    }
+
+    // Umumkan perpindahan token utama. Employee yang dibayar dengan token lama ikut pindah saat
+    // `execute_token_migration` dipanggil setelah timelock.
+    pub fn migrate_token(env: Env, new_token: Address, conversion_rate: Option<i128>) -> Result<TokenMigration, Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        Self::require_open(&env)?;
+        let from = Self::read_token(&env)?;
+        if new_token == from {
+            return Err(Error::TokenInUse);
+        }
+        if conversion_rate.is_some_and(|rate| rate <= 0) {
+            return Err(Error::InvalidAmount);
+        }
+        let now = env.ledger().timestamp();
+        let migration = TokenMigration {
+            from,
+            to: new_token,
+            conversion_rate,
+            announced_at: now,
+            executable_at: now.saturating_add(TOKEN_MIGRATION_DELAY_SECONDS),
+        };
+        env.storage().instance().set(&DataKey::TokenMigration, &migration);
+        env.events().publish((symbol_short!("tok_migr"), &employer), migration.clone());
+        Ok(migration)
+    }
+
+    pub fn cancel_token_migration(env: Env) -> Result<(), Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        if !env.storage().instance().has(&DataKey::TokenMigration) {
+            return Err(Error::NoPendingTokenMigration);
+        }
+        env.storage().instance().remove(&DataKey::TokenMigration);
+        env.events().publish((symbol_short!("tok_cncl"), &employer), ());
+        Ok(())
+    }
+
+    pub fn get_token_migration(env: Env) -> Option<TokenMigration> {
+        env.storage().instance().get(&DataKey::TokenMigration)
+    }
+
+    // Pindahkan token utama dan semua employee di token lama ke token baru. Dengan
+    // `conversion_rate`, saldo dan rate gaji dikonversi (rate dibulatkan ke atas); tanpa itu,
+    // semua saldo di token lama harus sudah dibayar. Gaji fiat tidak dikonversi, hanya pindah token.
+    pub fn execute_token_migration(env: Env) -> Result<u32, Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        Self::require_open(&env)?;
+        let migration: TokenMigration = env
+            .storage()
+            .instance()
+            .get(&DataKey::TokenMigration)
+            .ok_or(Error::NoPendingTokenMigration)?;
+        if env.ledger().timestamp() < migration.executable_at {
+            return Err(Error::TokenMigrationTimelocked);
+        }
+        if Self::read_total_debt(&env, &migration.from) > 0 {
+            return Err(Error::OutstandingWageDebt);
+        }
+        let (from, to) = (migration.from.clone(), migration.to.clone());
+        let now = Self::accrual_now(&env);
+        let convert = |amount: i128, round_up: bool| -> Result<i128, Error> {
+            match migration.conversion_rate {
+                None => Ok(amount),
+                Some(rate) => {
+                    let num = amount.checked_mul(rate).ok_or(Error::Overflow)?;
+                    if round_up { Self::ceil_div(num, RATE_SCALE) } else { Ok(num / RATE_SCALE) }
+                }
+            }
+        };
+
+        let list: Vec<Address> = env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(&env));
+        let mut migrated: u32 = 0;
+        for addr in list.iter() {
+            let mut emp = match Self::read_employee(&env, &addr) { Ok(v) => v, Err(_) => continue };
+            if emp.token != from {
+                continue;
+            }
+            Self::settle_accrual(&mut emp, now)?;
+            match &emp.wage_currency {
+                Some(currency) => {
+                    Self::fx_rate(&env, currency, &to)?;
+                    Self::register_fiat_currency(&env, &to, currency);
+                }
+                None if migration.conversion_rate.is_none() => {
+                    if emp.accrued_balance > 0 {
+                        return Err(Error::UnsettledBalance);
+                    }
+                }
+                None => {
+                    // Konversi nilai penuh termasuk sisa pecahan, lalu pecah lagi per periode
+                    let period = i128::from(Self::period_segment(emp.wage_period, emp.last_accrual_timestamp).1);
+                    let value = emp
+                        .accrued_balance
+                        .checked_mul(period)
+                        .ok_or(Error::Overflow)?
+                        .checked_add(emp.accrual_remainder)
+                        .ok_or(Error::Overflow)?;
+                    let converted = convert(value, false)?;
+                    emp.accrued_balance = converted / period;
+                    emp.accrual_remainder = converted % period;
+                    emp.wage_rate = convert(emp.wage_rate, true)?;
+                    let mut schedule = Vec::new(&env);
+                    for change in emp.scheduled_rates.iter() {
+                        schedule.push_back(RateChange {
+                            effective_at: change.effective_at,
+                            wage_rate: convert(change.wage_rate, true)?,
+                        });
+                    }
+                    emp.scheduled_rates = schedule;
+                    let proposal_key = DataKey::WageProposal(addr.clone());
+                    if let Some(mut proposal) = env.storage().persistent().get::<_, WageProposal>(&proposal_key) {
+                        proposal.wage_rate = convert(proposal.wage_rate, true)?;
+                        env.storage().persistent().set(&proposal_key, &proposal);
+                    }
+                    Self::push_rate_history(&env, &addr, RateChange { effective_at: now, wage_rate: emp.wage_rate });
+                }
+            }
+            emp.token = to.clone();
+            Self::write_employee(&env, &addr, &emp);
+            migrated = migrated.checked_add(1).ok_or(Error::Overflow)?;
+        }
+
+        // Agregat kedua token dihitung ulang dari record yang sudah dipindah. Dana terbatas di
+        // token lama tetap tercatat lewat `RestrictedTokens` sampai dilepas atau di-refund.
+        let mut allowed = Vec::new(&env);
+        for token in Self::read_allowed_tokens(&env).iter() {
+            if token != from && token != to {
+                allowed.push_back(token);
+            }
+        }
+        allowed.push_back(to.clone());
+        env.storage().instance().set(&DataKey::AllowedTokens, &allowed);
+        for token in [from.clone(), to.clone()] {
+            for key in Self::totals_keys(&env, &token).iter() {
+                let totals = Self::recompute_totals(&env, &key, now)?;
+                env.storage().instance().set(&key, &totals);
+            }
+        }
+        env.storage().instance().set(&DataKey::Token, &to);
+        env.storage().instance().remove(&DataKey::TokenMigration);
+
+        env.events().publish(
+            (Symbol::new(&env, "token_migrated"), &employer),
+            (from, to, migration.conversion_rate, migrated),
+        );
+        Ok(migrated)
+    }
+
+    pub fn get_allowed_tokens(env: Env) -> Vec<Address> {
+        Self::read_allowed_tokens(&env)
+    }
+
+    // Pindah token pembayaran butuh persetujuan employee dan saldo lama harus sudah dibayar,
+    // karena accrued_balance tidak bisa dikonversi antar token
+    pub fn set_payout_token(env: Env, caller: Address, employee_address: Address, token: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        employee_address.require_auth();
+        Self::require_allowed_token(&env, &token)?;
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        if data.token == token {
+            return Ok(());
+        }
+        let now = Self::accrual_now(&env);
+        if Self::settle_accrual(&mut data, now)? > 0
+            || env.storage().persistent().has(&DataKey::WageDebt(employee_address.clone()))
+        {
+            return Err(Error::UnsettledBalance);
+        }
+
+        Self::settle_overcount(&env, &employee_address, &data, now)?;
+        let old_key = Self::totals_key(&data);
+        let old_token = data.token.clone();
+        data.token = token.clone();
+        if let Some(currency) = &data.wage_currency {
+            Self::fx_rate(&env, currency, &token)?;
+            Self::register_fiat_currency(&env, &token, currency);
+        }
+        if data.active {
+            let rate = Self::employee_rate_per_second_scaled(&data)?;
+            Self::adjust_totals(&env, &old_key, now, -rate, 0)?;
+            Self::adjust_totals(&env, &Self::totals_key(&data), now, rate, 0)?;
+        }
+        Self::write_employee(&env, &employee_address, &data);
+        env.events().publish((symbol_short!("pay_token"), &employee_address), (old_token, token));
+        Ok(())
+    }
+
+    // Oracle harga gaya SEP-40 untuk mengonversi gaji fiat ke token saat dibayar
+    pub fn set_price_oracle(
+        env: Env,
+        caller: Address,
+        oracle: Address,
+        max_age_seconds: u64,
+        max_deviation_bps: u32,
+    ) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::Treasury)?;
+        let config = OracleConfig { oracle, max_age_seconds, max_deviation_bps };
+        env.storage().instance().set(&DataKey::Oracle, &config);
+        env.events().publish((symbol_short!("oracle"), &caller), config);
         Ok(())
     }
 
-    pub fn add_employee(
+    pub fn get_price_oracle(env: Env) -> Option<OracleConfig> {
+        env.storage().instance().get(&DataKey::Oracle)
+    }
+
+    // Lupakan kurs acuan supaya pergerakan harga yang memang sah tidak terus ditolak cek deviasi
+    pub fn reset_fx_rate(env: Env, caller: Address, currency: Symbol, token: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::Treasury)?;
+        env.storage().instance().remove(&DataKey::FxRate(currency.clone(), token.clone()));
+        env.events().publish((symbol_short!("fx_reset"), &caller), (currency, token));
+        Ok(())
+    }
+
+    pub fn get_fx_rate(env: Env, currency: Symbol, token: Address) -> Option<i128> {
+        env.storage().instance().get(&DataKey::FxRate(currency, token))
+    }
+
+    // Ubah denominasi gaji ke mata uang fiat (atau kembali ke token dengan `None`) sekaligus
+    // rate barunya. Seperti ganti token, butuh persetujuan employee dan saldo lama harus lunas.
+    pub fn set_wage_currency(
         env: Env,
+        caller: Address,
         employee_address: Address,
+        currency: Option<Symbol>,
         wage_rate: i128,
-        wage_period: u32,
     ) -> Result<(), Error> {
-        let _employer = Self::require_employer_auth(&env)?;
-        if wage_rate <= 0 {
-            return Err(Error::InvalidWageRate);
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        employee_address.require_auth();
+        if wage_rate <= 0 { return Err(Error::InvalidWageRate); }
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        let now = Self::accrual_now(&env);
+        if Self::settle_accrual(&mut data, now)? > 0
+            || env.storage().persistent().has(&DataKey::WageDebt(employee_address.clone()))
+        {
+            return Err(Error::UnsettledBalance);
         }
-        if wage_period > WAGE_PERIOD_MONTH {
-            return Err(Error::InvalidWagePeriod);
+        // Rate terjadwal tercatat dalam denominasi lama
+        if !data.scheduled_rates.is_empty() {
+            return Err(Error::InvalidRateChange);
         }
-        let key = DataKey::Employee(employee_address.clone());
-        if env.storage().persistent().has(&key) {
-            return Err(Error::EmployeeAlreadyExists);
+        if let Some(currency) = &currency {
+            Self::fx_rate(&env, currency, &data.token)?;
+            Self::register_fiat_currency(&env, &data.token, currency);
         }
-        let data = Employee {
-            wage_rate,
-            last_accrual_timestamp: env.ledger().timestamp(),
-            wage_period,
-            accrued_balance: 0,
-            active: true,
-        };
-        env.storage().persistent().set(&key, &data);
-
-        let mut list: Vec<Address> = env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(&env));
-        list.push_back(employee_address.clone());
-        env.storage().persistent().set(&DataKey::EmployeeList, &list);
 
-        env.events().publish((symbol_short!("hire"), &employee_address), data.wage_rate);
+        Self::settle_overcount(&env, &employee_address, &data, now)?;
+        let old_key = Self::totals_key(&data);
+        let old_rate = if data.active { Self::employee_rate_per_second_scaled(&data)? } else { 0 };
+        data.wage_currency = currency.clone();
+        data.wage_rate = wage_rate;
+        data.accrual_remainder = 0;
+        if data.active {
+            Self::adjust_totals(&env, &old_key, now, -old_rate, 0)?;
+            Self::adjust_totals(&env, &Self::totals_key(&data), now, Self::employee_rate_per_second_scaled(&data)?, 0)?;
+        }
+        Self::write_employee(&env, &employee_address, &data);
+        env.storage().persistent().remove(&DataKey::WageProposal(employee_address.clone()));
+        Self::push_rate_history(&env, &employee_address, RateChange { effective_at: now, wage_rate });
+        env.events().publish((symbol_short!("wage_ccy"), &employee_address), (currency, wage_rate));
         Ok(())
     }
 
-    pub fn update_wage_rate(env: Env, employee_address: Address, new_wage_rate: i128) -> Result<(), Error> {
-        let _employer = Self::require_employer_auth(&env)?;
-        if new_wage_rate <= 0 { return Err(Error::InvalidWageRate); }
-
-        let key = DataKey::Employee(employee_address.clone());
-        let mut data: Employee = env.storage().persistent().get(&key).ok_or(Error::EmployeeNotFound)?;
-        let now = env.ledger().timestamp();
-        let accrued = Self::calculate_accrued(&data, now)?;
-        data.accrued_balance = data.accrued_balance.checked_add(accrued).ok_or(Error::Overflow)?;
-        data.wage_rate = new_wage_rate;
-        data.last_accrual_timestamp = now;
-        env.storage().persistent().set(&key, &data);
-        env.events().publish((symbol_short!("wage_set"), &employee_address), new_wage_rate);
+    pub fn deposit(env: Env, caller: Address, token: Address, amount: i128) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::Treasury)?;
+        Self::require_not_paused(&env)?;
+        Self::require_open(&env)?;
+        Self::require_allowed_token(&env, &token)?;
+        if amount <= 0 { return Err(Error::InvalidAmount); }
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&caller, &env.current_contract_address(), &amount);
+        env.events().publish((symbol_short!("deposit"), &caller, &token), amount);
+        // Utang gaji dari mode insolvensi dilunasi lebih dulu dari deposit baru
+        Self::settle_wage_debts(&env, &token)?;
         Ok(())
     }
 
-    pub fn freeze_employee(env: Env, employee_address: Address) -> Result<(), Error> {
-        let _employer = Self::require_employer_auth(&env)?;
-        let key = DataKey::Employee(employee_address.clone());
-        let mut data: Employee = env.storage().persistent().get(&key).ok_or(Error::EmployeeNotFound)?;
-        
-        // Hitung dan bayar semua accrued wages
-        let now = env.ledger().timestamp();
-        let accrued = Self::calculate_accrued(&data, now)?;
-        let total_owed = data.accrued_balance.checked_add(accrued).ok_or(Error::Overflow)?;
-        
-        if total_owed > 0 {
-            // Bayar semua accrued wages
-            let token_addr = Self::read_token(&env)?;
-            let token_client = token::Client::new(&env, &token_addr);
-            let contract_balance = token_client.balance(&env.current_contract_address());
-            if total_owed > contract_balance { return Err(Error::InsufficientContractBalance); }
-            
-            // Reset accrued balance dan update timestamp
-            data.accrued_balance = 0;
-            data.last_accrual_timestamp = now;
-            
-            // Transfer ke karyawan
-            token_client.transfer(&env.current_contract_address(), &employee_address, &total_owed);
-            env.events().publish((symbol_short!("final_pay"), &employee_address), total_owed);
-        }
-        
-        // Set employee sebagai non-aktif
-        data.active = false;
-        env.storage().persistent().set(&key, &data);
-        
-        env.events().publish((symbol_short!("freeze"), &employee_address), ());
-        Ok(())
-    }
+    // Pendanaan dari pihak ketiga (klien, program hibah, induk perusahaan). Siapa saja boleh
+    // memanggil; total per funder dicatat dan event `fund` membawa memo untuk rekonsiliasi.
+    // Kalau funder punya batasan, dananya disisihkan dan hanya dipakai untuk employee yang
+    // diizinkan lewat jalur pembayaran normal. Distribusi pro-rata dan pelunasan utang gaji
+    // tidak menyentuhnya; saat wind-down sisanya dikembalikan ke funder.
+    pub fn fund(env: Env, from: Address, token: Address, amount: i128, memo: Option<String>) -> Result<(), Error> {
+        from.require_auth();
+        Self::require_not_paused(&env)?;
+        // Setelah wind-down saldo sudah dibekukan ke pool klaim; dana baru akan terdampar
+        Self::require_not_wound_down(&env)?;
+        Self::require_open(&env)?;
+        Self::require_allowed_token(&env, &token)?;
+        if amount <= 0 { return Err(Error::InvalidAmount); }
+        token::Client::new(&env, &token).transfer(&from, &env.current_contract_address(), &amount);
 
-    pub fn activate_employee(env: Env, employee_address: Address) -> Result<(), Error> {
-        let _employer = Self::require_employer_auth(&env)?;
-        let key = DataKey::Employee(employee_address.clone());
-        let mut data: Employee = env.storage().persistent().get(&key).ok_or(Error::EmployeeNotFound)?;
-        
-        // Set employee sebagai aktif
-        data.active = true;
-        env.storage().persistent().set(&key, &data);
-        
-        env.events().publish((symbol_short!("activate"), &employee_address), ());
+        let total_key = DataKey::FunderTotal(from.clone(), token.clone());
+        let total: i128 = env.storage().persistent().get(&total_key).unwrap_or(0);
+        let total = total.checked_add(amount).ok_or(Error::Overflow)?;
+        env.storage().persistent().set(&total_key, &total);
+
+        let restricted = env.storage().persistent().has(&DataKey::FundingRestriction(from.clone()));
+        if restricted {
+            let key = DataKey::RestrictedBalance(from.clone(), token.clone());
+            let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+            env.storage().persistent().set(&key, &balance.checked_add(amount).ok_or(Error::Overflow)?);
+            let mut funders = Self::read_restricted_funders(&env, &token);
+            if !funders.contains(&from) {
+                if funders.len() >= MAX_RESTRICTED_FUNDERS {
+                    return Err(Error::TooManyRestrictedFunders);
+                }
+                funders.push_back(from.clone());
+                Self::write_restricted_funders(&env, &token, &funders);
+            }
+            let restricted_total = Self::read_restricted_total(&env, &token)
+                .checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            env.storage().instance().set(&DataKey::RestrictedTotal(token.clone()), &restricted_total);
+        }
+        env.events().publish((symbol_short!("fund"), &from, &token), (amount, total, restricted, memo));
+        if !restricted {
+            Self::settle_wage_debts(&env, &token)?;
+        }
         Ok(())
     }
 
-    pub fn remove_employee(env: Env, employee_address: Address) -> Result<(), Error> {
-        let _employer = Self::require_employer_auth(&env)?;
-        let key = DataKey::Employee(employee_address.clone());
-        let data: Employee = env.storage().persistent().get(&key).ok_or(Error::EmployeeNotFound)?;
-        
-        // Hanya bisa remove employee yang sudah di-freeze (non-aktif)
-        if data.active { return Err(Error::CannotRemoveActiveEmployee); }
-        
-        env.storage().persistent().remove(&key);
-
-        if let Some(list) = env.storage().persistent().get::<_, Vec<Address>>(&DataKey::EmployeeList) {
-            let mut new_list = Vec::new(&env);
-            for addr in list.iter() {
-                if addr != employee_address {
-                    new_list.push_back(addr.clone());
+    // Batasan dana disetujui bersama oleh funder dan treasury, supaya daftar funder terbatas
+    // yang diperiksa setiap pembayaran tidak bisa diisi alamat sembarang. Menghapus batasan
+    // (None) melepas sisa dana terbatas funder ini menjadi dana umum payroll.
+    pub fn set_funding_restriction(
+        env: Env,
+        caller: Address,
+        funder: Address,
+        restriction: Option<FundingRestriction>,
+    ) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::Treasury)?;
+        funder.require_auth();
+        Self::require_not_paused(&env)?;
+        Self::require_open(&env)?;
+        let key = DataKey::FundingRestriction(funder.clone());
+        match &restriction {
+            Some(r) => {
+                if r.employees.len() > MAX_FUNDING_RESTRICTION_ENTRIES
+                    || r.cost_centres.len() > MAX_FUNDING_RESTRICTION_ENTRIES
+                {
+                    return Err(Error::FundingRestrictionTooLarge);
+                }
+                env.storage().persistent().set(&key, r)
+            }
+            None => {
+                env.storage().persistent().remove(&key);
+                for token in Self::read_restricted_tokens(&env).iter() {
+                    let released = Self::release_restricted(&env, &token, &funder)?;
+                    if released > 0 {
+                        Self::settle_wage_debts(&env, &token)?;
+                    }
                 }
             }
-            env.storage().persistent().set(&DataKey::EmployeeList, &new_list);
         }
-
-        env.events().publish((symbol_short!("fire"), &employee_address), ());
+        env.events().publish((symbol_short!("fund_rule"), &funder), restriction);
         Ok(())
     }
 
-    pub fn list_employees(env: Env) -> Vec<Address> {
-        env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(&env))
+    pub fn get_funding_restriction(env: Env, funder: Address) -> Option<FundingRestriction> {
+        env.storage().persistent().get(&DataKey::FundingRestriction(funder))
     }
 
-    pub fn get_live_accrued_balance(env: Env, employee_address: Address) -> Result<i128, Error> {
-        let data = Self::read_employee(&env, &employee_address)?;
-        let now = env.ledger().timestamp();
-        let accrued = Self::calculate_accrued(&data, now)?;
-        data.accrued_balance.checked_add(accrued).ok_or(Error::Overflow)
+    pub fn get_funder_total(env: Env, funder: Address, token: Address) -> i128 {
+        env.storage().persistent().get(&DataKey::FunderTotal(funder, token)).unwrap_or(0)
     }
 
-    pub fn deposit(env: Env, amount: i128) -> Result<(), Error> {
-        let employer = Self::require_employer_auth(&env)?;
-        if amount <= 0 { return Err(Error::InvalidAmount); }
-        let token_addr = Self::read_token(&env)?;
-        let token_client = token::Client::new(&env, &token_addr);
-        token_client.transfer(&employer, &env.current_contract_address(), &amount);
-        env.events().publish((symbol_short!("deposit"), &employer), amount);
+    pub fn get_restricted_balance(env: Env, funder: Address, token: Address) -> i128 {
+        env.storage().persistent().get(&DataKey::RestrictedBalance(funder, token)).unwrap_or(0)
+    }
+
+    pub fn set_cost_centre(env: Env, caller: Address, employee_address: Address, cost_centre: Option<Symbol>) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::require_not_paused(&env)?;
+        Self::read_employee(&env, &employee_address)?;
+        let key = DataKey::CostCentre(employee_address.clone());
+        match &cost_centre {
+            Some(centre) => env.storage().persistent().set(&key, centre),
+            None => env.storage().persistent().remove(&key),
+        }
+        env.events().publish((symbol_short!("cost_ctr"), &employee_address), cost_centre);
         Ok(())
     }
 
-    pub fn withdraw_surplus(env: Env, amount: i128) -> Result<(), Error> {
-        let employer = Self::require_employer_auth(&env)?;
+    pub fn get_cost_centre(env: Env, employee_address: Address) -> Option<Symbol> {
+        env.storage().persistent().get(&DataKey::CostCentre(employee_address))
+    }
+
+    // Surplus selalu dikirim ke employer (owner), bukan ke operator treasury.
+    // Token di luar allowlist juga boleh ditarik supaya dana nyasar tidak terkunci.
+    pub fn withdraw_surplus(env: Env, caller: Address, token: Address, amount: i128) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::Treasury)?;
+        Self::require_not_paused(&env)?;
+        let employer = Self::read_employer(&env)?;
         if amount <= 0 { return Err(Error::InvalidAmount); }
-        let token_addr = Self::read_token(&env)?;
-        let token_client = token::Client::new(&env, &token_addr);
+        let token_client = token::Client::new(&env, &token);
         let contract_balance = token_client.balance(&env.current_contract_address());
         if amount > contract_balance { return Err(Error::InsufficientContractBalance); }
+        // Jangan biarkan employer menarik dana yang sudah menjadi hak employee
+        if amount > Self::free_surplus(&env, &token, contract_balance)? {
+            return Err(Error::SurplusWouldUnderfundWages);
+        }
         token_client.transfer(&env.current_contract_address(), &employer, &amount);
-        env.events().publish((symbol_short!("surplus"), &employer), amount);
+        env.events().publish((symbol_short!("surplus"), &employer, &token), amount);
         Ok(())
     }
 
     pub fn withdraw(env: Env, employee_address: Address, amount: i128) -> Result<(), Error> {
         employee_address.require_auth();
+        Self::require_not_paused(&env)?;
+        Self::require_not_wound_down(&env)?;
         if amount <= 0 { return Err(Error::InvalidAmount); }
         let key = DataKey::Employee(employee_address.clone());
         let mut data: Employee = env.storage().persistent().get(&key).ok_or(Error::EmployeeNotFound)?;
-        let now = env.ledger().timestamp();
+        Self::require_no_wage_debt(&env, &data.token)?;
+        let now = Self::accrual_now(&env);
+        Self::end_term_if_due(&env, &employee_address, &mut data, now)?;
         Self::apply_withdrawal(&mut data, amount, now)?;
         Self::write_employee(&env, &employee_address, &data);
-        let token_addr = Self::read_token(&env)?;
-        let token_client = token::Client::new(&env, &token_addr);
-        token_client.transfer(&env.current_contract_address(), &employee_address, &amount);
-        env.events().publish((symbol_short!("withdraw"), &employee_address), amount);
+        Self::adjust_totals(&env, &Self::totals_key(&data), now, 0, -amount)?;
+        // Gaji fiat: `amount` dalam mata uang referensi, dikonversi dengan kurs saat ini
+        let payout = Self::payout_amount(&env, &data, amount)?;
+        Self::pay_out(&env, &data.token, &employee_address, payout)?;
+        env.events().publish((symbol_short!("withdraw"), &employee_address), payout);
+        Self::check_low_runway(&env, &data.token);
         Ok(())
     }
 
-    pub fn pay_partial_by_employer(env: Env, employee_address: Address, amount: i128) -> Result<(), Error> {
-        let _employer = Self::require_employer_auth(&env)?;
+    pub fn pay_partial_by_employer(env: Env, caller: Address, employee_address: Address, amount: i128) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::require_not_paused(&env)?;
         if amount <= 0 { return Err(Error::InvalidAmount); }
         let key = DataKey::Employee(employee_address.clone());
         let mut data: Employee = env.storage().persistent().get(&key).ok_or(Error::EmployeeNotFound)?;
-        let now = env.ledger().timestamp();
+        Self::require_no_wage_debt(&env, &data.token)?;
+        let now = Self::accrual_now(&env);
         Self::apply_withdrawal(&mut data, amount, now)?;
         Self::write_employee(&env, &employee_address, &data);
-        let token_addr = Self::read_token(&env)?;
-        let token_client = token::Client::new(&env, &token_addr);
-        let contract_balance = token_client.balance(&env.current_contract_address());
-        if amount > contract_balance { return Err(Error::InsufficientContractBalance); }
-        token_client.transfer(&env.current_contract_address(), &employee_address, &amount);
-        env.events().publish((symbol_short!("pay_part"), &employee_address), amount);
+        Self::adjust_totals(&env, &Self::totals_key(&data), now, 0, -amount)?;
+        let payout = Self::payout_amount(&env, &data, amount)?;
+        Self::pay_out(&env, &data.token, &employee_address, payout)?;
+        env.events().publish((symbol_short!("pay_part"), &employee_address), payout);
+        Self::check_low_runway(&env, &data.token);
         Ok(())
     }
 
-    pub fn payday_sweep(env: Env, employee_address: Address) -> Result<(), Error> {
-        let _employer = Self::require_employer_auth(&env)?;
+    pub fn payday_sweep(env: Env, caller: Address, employee_address: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::require_not_paused(&env)?;
         let mut data = Self::read_employee(&env, &employee_address)?;
+        Self::require_no_wage_debt(&env, &data.token)?;
 
-        let now = env.ledger().timestamp();
-        let accrued = Self::calculate_accrued(&data, now)?;
-        let total_owed = data.accrued_balance.checked_add(accrued).ok_or(Error::Overflow)?;
+        let now = Self::accrual_now(&env);
+        Self::end_term_if_due(&env, &employee_address, &mut data, now)?;
+        let total_owed = Self::settle_accrual(&mut data, now)?;
         if total_owed <= 0 { return Err(Error::NothingToWithdraw); }
 
-        let token_addr = Self::read_token(&env)?;
-        let token_client = token::Client::new(&env, &token_addr);
-        let contract_balance = token_client.balance(&env.current_contract_address());
-        if total_owed > contract_balance { return Err(Error::InsufficientContractBalance); }
-
+        let payout = Self::payout_amount(&env, &data, total_owed)?;
         data.accrued_balance = 0;
-        data.last_accrual_timestamp = now;
         Self::write_employee(&env, &employee_address, &data);
+        Self::adjust_totals(&env, &Self::totals_key(&data), now, 0, -total_owed)?;
 
-        token_client.transfer(&env.current_contract_address(), &employee_address, &total_owed);
-        env.events().publish((symbol_short!("sweep"), &employee_address), total_owed);
+        Self::pay_out(&env, &data.token, &employee_address, payout)?;
+        env.events().publish((symbol_short!("sweep"), &employee_address), payout);
+        Self::check_low_runway(&env, &data.token);
         Ok(())
     }
 
-    pub fn payday_sweep_many(env: Env, employees: Vec<Address>) -> Result<BatchPayResult, Error> {
-        let _employer = Self::require_employer_auth(&env)?;
-        let now = env.ledger().timestamp();
+    // Employee dengan token berbeda boleh dicampur; saldo dan utang gaji dicek per token
+    pub fn payday_sweep_many(env: Env, caller: Address, employees: Vec<Address>) -> Result<BatchPayResult, Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
+        Self::require_not_paused(&env)?;
+        let now = Self::accrual_now(&env);
 
+        // Dalam satuan token, untuk cek saldo
+        let mut owed_by_token: Map<Address, i128> = Map::new(&env);
         let mut total: i128 = 0;
         let mut count: u32 = 0;
         for i in 0..employees.len() {
@@ -407,7 +3199,10 @@ impl FairWage {
                     .checked_add(Self::calculate_accrued(&emp, now)?)
                     .ok_or(Error::Overflow)?;
                 if owed > 0 {
-                    total = total.checked_add(owed).ok_or(Error::Overflow)?;
+                    let payout = Self::payout_amount(&env, &emp, owed)?;
+                    let token_owed = owed_by_token.get(emp.token.clone()).unwrap_or(0);
+                    owed_by_token.set(emp.token.clone(), token_owed.checked_add(payout).ok_or(Error::Overflow)?);
+                    total = total.checked_add(payout).ok_or(Error::Overflow)?;
                     count = count.checked_add(1).ok_or(Error::Overflow)?;
                 }
             }
@@ -416,35 +3211,353 @@ impl FairWage {
             return Err(Error::NothingToWithdraw);
         }
 
-        let token_addr = Self::read_token(&env)?;
-        let token_client = token::Client::new(&env, &token_addr);
-        let contract_balance = token_client.balance(&env.current_contract_address());
-        if total > contract_balance {
-            return Err(Error::InsufficientContractBalance);
+        // Likuiditas dicek per employee di `pay_out`, karena dana funder bisa dibatasi per employee
+        for token in owed_by_token.keys().iter() {
+            Self::require_no_wage_debt(&env, &token)?;
         }
 
         for i in 0..employees.len() {
             let addr = employees.get(i).unwrap();
             let mut emp = match Self::read_employee(&env, &addr) { Ok(v) => v, Err(_) => continue };
-            let owed = emp
-                .accrued_balance
-                .checked_add(Self::calculate_accrued(&emp, now)?)
+            let ended = Self::end_term_if_due(&env, &addr, &mut emp, now)?;
+            let owed = Self::settle_accrual(&mut emp, now)?;
+            if owed <= 0 {
+                if ended {
+                    Self::write_employee(&env, &addr, &emp);
+                }
+                continue;
+            }
+
+            let payout = Self::payout_amount(&env, &emp, owed)?;
+            emp.accrued_balance = 0;
+            Self::write_employee(&env, &addr, &emp);
+            Self::adjust_totals(&env, &Self::totals_key(&emp), now, 0, -owed)?;
+            Self::pay_out(&env, &emp.token, &addr, payout)?;
+            env.events().publish((symbol_short!("sweep"), &addr), payout);
+        }
+        for token in owed_by_token.keys().iter() {
+            Self::check_low_runway(&env, &token);
+        }
+
+        Ok(BatchPayResult { paid_count: count, total_amount: total, paid_by_token: owed_by_token })
+    }
+
+    // Siapa pun boleh mencatat bahwa saldo kontrak tidak cukup menutup kewajiban;
+    // ini memulai masa tenggang sebelum distribusi pro-rata bisa dipicu publik
+    // Mengembalikan awal shortfall, atau None (dan menghapus catatan lama) kalau kontrak solven
+    pub fn report_shortfall(env: Env, token: Address) -> Result<Option<u64>, Error> {
+        let now = Self::accrual_now(&env);
+        let key = DataKey::TokenShortfall(token.clone());
+        let balance = Self::contract_token_balance(&env, &token);
+        if Self::total_liabilities(&env, &token, now)? <= balance {
+            env.storage().instance().remove(&key);
+            return Ok(None);
+        }
+        if let Some(since) = env.storage().instance().get::<_, u64>(&key) {
+            return Ok(Some(since));
+        }
+        env.storage().instance().set(&key, &now);
+        env.events().publish((symbol_short!("shortfall"), &token), now);
+        Ok(Some(now))
+    }
+
+    // Bagi saldo satu token ke employee yang dibayar dengan token itu, sebanding dengan gaji
+    // terutang; sisanya jadi utang gaji
+    pub fn distribute_pro_rata(env: Env, caller: Address, token: Address) -> Result<BatchPayResult, Error> {
+        caller.require_auth();
+        Self::require_not_paused(&env)?;
+        Self::require_not_wound_down(&env)?;
+        let now = Self::accrual_now(&env);
+        let employer = Self::read_employer(&env)?;
+        let privileged = caller == employer || Self::has_role_internal(&env, &caller, Role::PayrollAdmin);
+        if !privileged {
+            let since: u64 = env
+                .storage()
+                .instance()
+                .get(&DataKey::TokenShortfall(token.clone()))
+                .ok_or(Error::InsolvencyGracePeriod)?;
+            if now < since.saturating_add(INSOLVENCY_GRACE_SECONDS) {
+                return Err(Error::InsolvencyGracePeriod);
+            }
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        let balance = token_client.balance(&env.current_contract_address());
+        // Utang lama dari distribusi sebelumnya ikut antre di depan; dana terbatas funder
+        // tetap disisihkan untuk peruntukannya
+        let existing_debt = Self::read_total_debt(&env, &token);
+        let available = balance
+            .checked_sub(existing_debt)
+            .and_then(|v| v.checked_sub(Self::read_restricted_total(&env, &token)))
+            .ok_or(Error::Overflow)?
+            .max(0);
+
+        let list: Vec<Address> = env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(&env));
+        let mut total_owed: i128 = 0;
+        for addr in list.iter() {
+            if let Ok(emp) = Self::read_employee(&env, &addr) {
+                if emp.token != token {
+                    continue;
+                }
+                let owed = emp
+                    .accrued_balance
+                    .checked_add(Self::calculate_accrued(&emp, now)?)
+                    .ok_or(Error::Overflow)?;
+                let owed = Self::payout_amount(&env, &emp, owed)?;
+                total_owed = total_owed.checked_add(owed).ok_or(Error::Overflow)?;
+            }
+        }
+        if total_owed <= available {
+            return Err(Error::NotInsolvent);
+        }
+
+        let mut paid_total: i128 = 0;
+        let mut unpaid_total: i128 = 0;
+        let mut count: u32 = 0;
+        for addr in list.iter() {
+            let mut emp = match Self::read_employee(&env, &addr) { Ok(v) => v, Err(_) => continue };
+            if emp.token != token { continue; }
+            let settled = Self::settle_accrual(&mut emp, now)?;
+            if settled <= 0 { continue; }
+
+            // Gaji fiat dikunci ke token dengan kurs saat ini; utang gaji selalu dalam token
+            let owed = Self::payout_amount(&env, &emp, settled)?;
+            let pay = owed.checked_mul(available).ok_or(Error::Overflow)? / total_owed;
+            let unpaid = owed.checked_sub(pay).ok_or(Error::Overflow)?;
+            emp.accrued_balance = 0;
+            Self::write_employee(&env, &addr, &emp);
+            Self::adjust_totals(&env, &Self::totals_key(&emp), now, 0, -settled)?;
+            Self::adjust_totals(&env, &DataKey::TokenTotals(token.clone()), now, 0, unpaid)?;
+            if unpaid > 0 {
+                Self::add_wage_debt(&env, &token, &addr, unpaid)?;
+                unpaid_total = unpaid_total.checked_add(unpaid).ok_or(Error::Overflow)?;
+            }
+            if pay > 0 {
+                token_client.transfer(&env.current_contract_address(), &addr, &pay);
+                env.events().publish((symbol_short!("pro_rata"), &addr), (pay, unpaid));
+                paid_total = paid_total.checked_add(pay).ok_or(Error::Overflow)?;
+                count = count.checked_add(1).ok_or(Error::Overflow)?;
+            }
+        }
+
+        env.storage().instance().remove(&DataKey::TokenShortfall(token.clone()));
+        env.events().publish((symbol_short!("insolvent"), &caller, &token), (paid_total, unpaid_total));
+        let mut paid_by_token = Map::new(&env);
+        paid_by_token.set(token, paid_total);
+        Ok(BatchPayResult { paid_count: count, total_amount: paid_total, paid_by_token })
+    }
+
+    // Tutup payroll: bayar final pay semua employee, kunci kontrak, kembalikan sisa saldo
+    // setiap token di allowlist ke employer
+    pub fn close_payroll(env: Env) -> Result<CloseOutSummary, Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        Self::require_not_paused(&env)?;
+        Self::require_open(&env)?;
+        let now = Self::accrual_now(&env);
+
+        let list: Vec<Address> = env.storage().persistent().get(&DataKey::EmployeeList).unwrap_or(Vec::new(&env));
+        let mut total_paid: Map<Address, i128> = Map::new(&env);
+        let mut count: u32 = 0;
+        for addr in list.iter() {
+            let mut emp = match Self::read_employee(&env, &addr) { Ok(v) => v, Err(_) => continue };
+            let debt_key = DataKey::WageDebt(addr.clone());
+            let debt: i128 = env.storage().persistent().get(&debt_key).unwrap_or(0);
+            let accrued = Self::settle_accrual(&mut emp, now)?;
+            let owed = Self::payout_amount(&env, &emp, accrued)?
+                .checked_add(debt)
                 .ok_or(Error::Overflow)?;
-            if owed <= 0 { continue; }
 
             emp.accrued_balance = 0;
-            emp.last_accrual_timestamp = now;
+            if emp.active {
+                emp.suspended_at = now;
+            }
+            emp.active = false;
             Self::write_employee(&env, &addr, &emp);
-            token_client.transfer(&env.current_contract_address(), &addr, &owed);
-            env.events().publish((symbol_short!("sweep"), &addr), owed);
+            env.storage().persistent().remove(&debt_key);
+
+            // Final pay lewat jalur normal supaya dana terbatas hanya membayar employee yang
+            // diizinkan funder-nya; saldo kurang membatalkan seluruh close-out
+            if owed > 0 {
+                Self::pay_out(&env, &emp.token, &addr, owed)?;
+                let token_owed = total_paid.get(emp.token.clone()).unwrap_or(0);
+                total_paid.set(emp.token.clone(), token_owed.checked_add(owed).ok_or(Error::Overflow)?);
+                env.events().publish((symbol_short!("final_pay"), &addr), owed);
+                count = count.checked_add(1).ok_or(Error::Overflow)?;
+            }
+        }
+
+        // Sisa dana terbatas dikembalikan ke funder-nya, bukan ke employer
+        Self::refund_restricted(&env)?;
+
+        let mut surplus_returned: Map<Address, i128> = Map::new(&env);
+        for token in Self::read_allowed_tokens(&env).iter() {
+            env.storage().persistent().set(&DataKey::TokenDebtList(token.clone()), &Vec::<Address>::new(&env));
+            env.storage().instance().set(&DataKey::TokenDebt(token.clone()), &0_i128);
+            for key in Self::totals_keys(&env, &token).iter() {
+                env.storage().instance().set(
+                    &key,
+                    &PayrollTotals {
+                        rate_per_second_scaled: 0,
+                        settled_accrued: 0,
+                        checkpoint_timestamp: now,
+                    },
+                );
+            }
+
+            // Semua kewajiban token ini sudah dibayar di atas, sisanya surplus
+            let surplus = Self::contract_token_balance(&env, &token);
+            if surplus > 0 {
+                token::Client::new(&env, &token).transfer(&env.current_contract_address(), &employer, &surplus);
+            }
+            surplus_returned.set(token, surplus.max(0));
+        }
+        env.storage().instance().set(&DataKey::Closed, &true);
+
+        let summary = CloseOutSummary {
+            paid_count: count,
+            total_paid,
+            surplus_returned,
+        };
+        env.events().publish((symbol_short!("closed"), &employer), summary.clone());
+        Ok(summary)
+    }
+
+    pub fn pause(env: Env) -> Result<(), Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        env.storage().instance().set(&DataKey::Paused, &true);
+        env.events().publish((symbol_short!("pause"), &employer), ());
+        Ok(())
+    }
+
+    pub fn unpause(env: Env) -> Result<(), Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        env.storage().instance().set(&DataKey::Paused, &false);
+        env.events().publish((symbol_short!("unpause"), &employer), ());
+        Ok(())
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&DataKey::Paused).unwrap_or(false)
+    }
+
+    pub fn is_closed(env: Env) -> bool {
+        env.storage().instance().get(&DataKey::Closed).unwrap_or(false)
+    }
+
+    // 0 mematikan dead man's switch
+    pub fn set_inactivity_window(env: Env, seconds: u64) -> Result<(), Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        env.storage().instance().set(&DataKey::InactivityWindow, &seconds);
+        env.events().publish((symbol_short!("inactive"), &employer), seconds);
+        Ok(())
+    }
+
+    // Siapa pun boleh mengunci wind-down setelah employer melewati inactivity window
+    pub fn trigger_wind_down(env: Env) -> Result<WindDown, Error> {
+        Self::latch_wind_down(&env)
+    }
+
+    // Setelah wind-down, employee mengklaim bagian pro-rata dari saldo yang tersisa.
+    // Sengaja tidak dicek pause: employer yang hilang tidak bisa unpause lagi
+    pub fn claim_wind_down_share(env: Env, employee_address: Address) -> Result<i128, Error> {
+        employee_address.require_auth();
+        let wd = Self::latch_wind_down(&env)?;
+        let mut data = Self::read_employee(&env, &employee_address)?;
+        let mut wd = wd;
+        let mut pool = wd.pools.get(data.token.clone()).ok_or(Error::TokenNotAllowed)?;
+
+        let debt_key = DataKey::WageDebt(employee_address.clone());
+        let debt: i128 = env.storage().persistent().get(&debt_key).unwrap_or(0);
+        let accrued = Self::settle_accrual(&mut data, wd.stopped_at)?;
+        // Gaji fiat dikonversi dengan kurs snapshot pool, bukan kurs oracle saat ini
+        let accrued_payout = match &data.wage_currency {
+            Some(currency) if accrued > 0 => {
+                let rate = pool.fx_rates.get(currency.clone()).ok_or(Error::OraclePriceUnavailable)?;
+                accrued.checked_mul(rate).ok_or(Error::Overflow)? / RATE_SCALE
+            }
+            _ => accrued,
+        };
+        let owed = accrued_payout.checked_add(debt).ok_or(Error::Overflow)?;
+        if owed <= 0 { return Err(Error::NothingToWithdraw); }
+
+        let pay = if pool.balance >= pool.total_owed || pool.total_owed <= 0 {
+            owed
+        } else {
+            owed.checked_mul(pool.balance).ok_or(Error::Overflow)? / pool.total_owed
+        };
+
+        data.accrued_balance = 0;
+        if data.active {
+            data.suspended_at = wd.stopped_at;
         }
+        data.active = false;
+        Self::write_employee(&env, &employee_address, &data);
+        if debt > 0 {
+            env.storage().persistent().remove(&debt_key);
+            let total_debt = Self::read_total_debt(&env, &data.token).checked_sub(debt).ok_or(Error::Overflow)?;
+            env.storage().instance().set(&DataKey::TokenDebt(data.token.clone()), &total_debt.max(0));
+        }
+        Self::adjust_totals(&env, &Self::totals_key(&data), wd.stopped_at, 0, -accrued)?;
+        Self::adjust_totals(&env, &DataKey::TokenTotals(data.token.clone()), wd.stopped_at, 0, -debt)?;
+        pool.claimed_owed = pool.claimed_owed.checked_add(owed).ok_or(Error::Overflow)?;
+        wd.pools.set(data.token.clone(), pool);
+        env.storage().instance().set(&DataKey::WindDown, &wd);
+
+        if pay > 0 {
+            let token_client = token::Client::new(&env, &data.token);
+            token_client.transfer(&env.current_contract_address(), &employee_address, &pay);
+        }
+        env.events().publish((symbol_short!("wd_claim"), &employee_address), (pay, owed));
+        Ok(pay)
+    }
+
+    // Saldo di atas bagian yang masih bisa diklaim employee (dibulatkan ke atas) dikembalikan ke
+    // employer; setelah masa klaim habis seluruh sisa saldo token ini boleh ditarik. Tidak lewat
+    // `require_employer_auth` karena semua panggilan role diblokir setelah wind-down.
+    pub fn reclaim_wind_down_surplus(env: Env, token: Address) -> Result<i128, Error> {
+        let employer = Self::read_employer(&env)?;
+        employer.require_auth();
+        let wd = Self::latch_wind_down(&env)?;
+        let pool = wd.pools.get(token.clone()).ok_or(Error::TokenNotAllowed)?;
+
+        let balance = Self::contract_token_balance(&env, &token);
+        let claim_deadline = wd.stopped_at.saturating_add(WIND_DOWN_CLAIM_PERIOD_SECONDS);
+        let reserved = if env.ledger().timestamp() >= claim_deadline {
+            0
+        } else {
+            let unclaimed = pool.total_owed.checked_sub(pool.claimed_owed).ok_or(Error::Overflow)?.max(0);
+            if pool.balance >= pool.total_owed || pool.total_owed <= 0 {
+                unclaimed
+            } else {
+                Self::ceil_div(unclaimed.checked_mul(pool.balance).ok_or(Error::Overflow)?, pool.total_owed)?
+            }
+        };
+        let amount = balance.checked_sub(reserved).ok_or(Error::Overflow)?;
+        if amount <= 0 { return Err(Error::NothingToWithdraw); }
+
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &employer, &amount);
+        env.events().publish((symbol_short!("wd_surpl"), &employer, &token), (amount, reserved));
+        Ok(amount)
+    }
+
+    pub fn get_wind_down(env: Env) -> Option<WindDown> {
+        env.storage().instance().get(&DataKey::WindDown)
+    }
+
+    pub fn get_wage_debt(env: Env, employee_address: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::WageDebt(employee_address))
+            .unwrap_or(0)
+    }
 
-        Ok(BatchPayResult { paid_count: count, total_amount: total })
+    pub fn get_total_wage_debt(env: Env, token: Address) -> i128 {
+        Self::read_total_debt(&env, &token)
     }
 
     pub fn get_accrued_balance(env: Env, employee_address: Address) -> Result<i128, Error> {
         let data = Self::read_employee(&env, &employee_address)?;
-        let now = env.ledger().timestamp();
+        let now = Self::accrual_now(&env);
         let current = Self::calculate_accrued(&data, now)?;
         data.accrued_balance.checked_add(current).ok_or(Error::Overflow)
     }
@@ -453,19 +3566,80 @@ impl FairWage {
         Self::read_employee(&env, &employee_address)
     }
 
-    pub fn get_contract_balance(env: Env) -> Result<i128, Error> {
-        let token_addr = Self::read_token(&env)?;
-        let token_client = token::Client::new(&env, &token_addr);
-        Ok(token_client.balance(&env.current_contract_address()))
+    pub fn get_contract_balance(env: Env, token: Address) -> i128 {
+        Self::contract_token_balance(&env, &token)
     }
 
-    pub fn fix_employee_timestamp(env: Env, employee_address: Address) -> Result<(), Error> {
-        let _employer = Self::require_employer_auth(&env)?;
+    pub fn get_total_liabilities(env: Env, token: Address) -> Result<i128, Error> {
+        Self::total_liabilities(&env, &token, Self::accrual_now(&env))
+    }
+
+    pub fn get_runway_seconds(env: Env, token: Address) -> Result<u64, Error> {
+        Self::runway_seconds(&env, &token, Self::contract_token_balance(&env, &token))
+    }
+
+    // Deposit tambahan (dalam `token`) yang dibutuhkan agar semua employee aktif terdanai sampai `timestamp`
+    pub fn required_deposit_until(env: Env, token: Address, timestamp: u64) -> Result<i128, Error> {
+        let now = Self::accrual_now(&env);
+        let totals = Self::combined_totals(&env, &token, Self::cap_to_stream_stop(&env, now.max(timestamp)))?;
+        let shortfall = totals
+            .settled_accrued
+            .checked_sub(Self::contract_token_balance(&env, &token))
+            .ok_or(Error::Overflow)?;
+        Ok(shortfall.max(0))
+    }
+
+    // Hanya employer: dana ditarik dari allowance milik employer sendiri. Allowance-nya diberikan
+    // terpisah lewat `approve` di kontrak token, dengan kontrak payroll ini sebagai spender.
+    pub fn set_auto_topup(env: Env, token: Address, max_per_period: i128, period_seconds: u64) -> Result<(), Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        Self::require_allowed_token(&env, &token)?;
+        if max_per_period <= 0 || period_seconds == 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let topup = AutoTopUp {
+            max_per_period,
+            period_seconds,
+            window_start: env.ledger().timestamp(),
+            pulled_in_window: 0,
+        };
+        env.storage().instance().set(&DataKey::AutoTopUp(token.clone()), &topup);
+        env.events().publish((symbol_short!("topup_set"), &employer, &token), (max_per_period, period_seconds));
+        Ok(())
+    }
+
+    pub fn disable_auto_topup(env: Env, token: Address) -> Result<(), Error> {
+        let employer = Self::require_employer_auth(&env)?;
+        env.storage().instance().remove(&DataKey::AutoTopUp(token.clone()));
+        env.events().publish((symbol_short!("topup_off"), &employer), token);
+        Ok(())
+    }
+
+    pub fn get_auto_topup(env: Env, token: Address) -> Option<AutoTopUp> {
+        env.storage().instance().get(&DataKey::AutoTopUp(token))
+    }
+
+    pub fn set_runway_threshold(env: Env, caller: Address, seconds: u64) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::Treasury)?;
+        env.storage().instance().set(&DataKey::RunwayThreshold, &seconds);
+        env.events().publish((symbol_short!("runway"), &caller), seconds);
+        Ok(())
+    }
+
+    pub fn get_withdrawable_surplus(env: Env, token: Address) -> Result<i128, Error> {
+        Self::free_surplus(&env, &token, Self::contract_token_balance(&env, &token))
+    }
+
+    pub fn fix_employee_timestamp(env: Env, caller: Address, employee_address: Address) -> Result<(), Error> {
+        Self::require_role(&env, &caller, Role::PayrollAdmin)?;
         let mut data = Self::read_employee(&env, &employee_address)?;
         if data.last_accrual_timestamp == 0 {
-            data.last_accrual_timestamp = env.ledger().timestamp();
+            data.last_accrual_timestamp = Self::accrual_now(&env);
             Self::write_employee(&env, &employee_address, &data);
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test;
@@ -1,98 +1,988 @@
 // Di dalam file: contracts/fungible/src/test.rs
 
-use crate::{FairWage, FairWageClient};
+use crate::{
+    DataKey, Error, FairWage, FairWageClient, FundingRestriction, LeavePolicy, LeaveType, Role, SeverancePolicy,
+    WagePeriod, YEAR_SECONDS,
+};
+use mock_price_oracle::{Asset, MockPriceOracle, MockPriceOracleClient};
 use soroban_sdk::{
-    testutils::{Address as _, Ledger, LedgerInfo, AuthorizedFunction, AuthorizedInvocation, MockAuth},
+    testutils::{Address as _, Ledger},
     token::{Client as TokenClient, StellarAssetClient},
-    Address, Env, IntoVal, Symbol, Vec,
+    vec, Address, Env, IntoVal, Map, String, Symbol, Val,
 };
 
-// Fungsi helper untuk membuat token
-fn register_token_contract<'a>(env: &'a Env, admin: &Address) -> (Address, TokenClient<'a>) {
-    let token_id = env.register_stellar_asset_contract(admin.clone());
-    (token_id, TokenClient::new(env, &token_id))
+const START: u64 = 1_700_000_000;
+
+struct Setup<'a> {
+    client: FairWageClient<'a>,
+    contract_id: Address,
+    employer: Address,
+    token_id: Address,
+    token: TokenClient<'a>,
+    minter: StellarAssetClient<'a>,
+}
+
+// Kontrak payroll + token, semua otorisasi di-mock
+fn setup(env: &Env) -> Setup<'_> {
+    env.mock_all_auths();
+    env.ledger().set_timestamp(START);
+    let contract_id = env.register(FairWage, ());
+    let client = FairWageClient::new(env, &contract_id);
+    let employer = Address::generate(env);
+    let token_id = env.register_stellar_asset_contract_v2(employer.clone()).address();
+    client.initialize(&employer, &token_id);
+    Setup {
+        client,
+        contract_id,
+        employer,
+        token: TokenClient::new(env, &token_id),
+        minter: StellarAssetClient::new(env, &token_id),
+        token_id,
+    }
+}
+
+fn hire(s: &Setup, env: &Env, wage_rate: i128, period: WagePeriod) -> Address {
+    let employee = Address::generate(env);
+    s.client.add_employee(&s.employer, &employee, &wage_rate, &period, &None, &None, &None);
+    employee
+}
+
+fn fund_contract(s: &Setup, amount: i128) {
+    s.minter.mint(&s.employer, &amount);
+    s.client.deposit(&s.employer, &s.token_id, &amount);
+}
+
+fn advance(env: &Env, seconds: u64) {
+    env.ledger().set_timestamp(env.ledger().timestamp() + seconds);
 }
 
 #[test]
 fn test_fair_wage_full_scenario() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, FairWage);
-    let client = FairWageClient::new(&env, &contract_id);
+    let s = setup(&env);
 
-    let employer = Address::generate(&env);
+    // --- 1. Tambah Karyawan: 100 token per detik ---
+    let employee = hire(&s, &env, 360_000, WagePeriod::Hour);
+
+    // --- 2 & 3. Mint Token ke Majikan lalu Deposit ---
+    s.minter.mint(&s.employer, &10_000);
+    assert_eq!(s.token.balance(&s.employer), 10_000);
+    s.client.deposit(&s.employer, &s.token_id, &5_000);
+    assert_eq!(s.token.balance(&s.contract_id), 5_000);
+    assert_eq!(s.token.balance(&s.employer), 5_000);
+
+    // --- 4. Majukan Waktu dan Cek Saldo Akrual ---
+    advance(&env, 10);
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 1_000);
+
+    // --- 5. Karyawan Menarik Gaji ---
+    s.client.withdraw(&employee, &700);
+    assert_eq!(s.token.balance(&employee), 700);
+    assert_eq!(s.token.balance(&s.contract_id), 4_300);
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 300);
+}
+
+// --- Gaji fiat lewat oracle harga (MockPriceOracle) ---
+
+const ORACLE_DECIMALS: u32 = 7;
+
+// --- Role ---
+
+#[test]
+fn test_roles_gate_each_entrypoint() {
+    let env = Env::default();
+    let s = setup(&env);
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let auditor = Address::generate(&env);
+    s.client.grant_role(&admin, &Role::PayrollAdmin);
+    s.client.grant_role(&treasury, &Role::Treasury);
+    s.client.grant_role(&auditor, &Role::Auditor);
+    s.minter.mint(&treasury, &10_000);
+    s.minter.mint(&admin, &10_000);
+    s.minter.mint(&auditor, &10_000);
+
+    // PayrollAdmin mengelola employee, tapi tidak memindahkan dana treasury
+    let employee = Address::generate(&env);
+    s.client.add_employee(&admin, &employee, &3_600, &WagePeriod::Hour, &None, &None, &None);
+    assert_eq!(s.client.try_deposit(&admin, &s.token_id, &100), Err(Ok(Error::NotAuthorized)));
+    assert_eq!(s.client.try_withdraw_surplus(&admin, &s.token_id, &1), Err(Ok(Error::NotAuthorized)));
+
+    // Treasury memegang dana, tapi tidak mengelola employee
+    s.client.deposit(&treasury, &s.token_id, &5_000);
+    let other = Address::generate(&env);
+    assert_eq!(
+        s.client.try_add_employee(&treasury, &other, &3_600, &WagePeriod::Hour, &None, &None, &None),
+        Err(Ok(Error::NotAuthorized)),
+    );
+    assert_eq!(s.client.try_freeze_employee(&treasury, &employee), Err(Ok(Error::NotAuthorized)));
+    s.client.withdraw_surplus(&treasury, &s.token_id, &1_000);
+
+    // Auditor hanya bisa membaca
+    assert_eq!(
+        s.client.try_add_employee(&auditor, &other, &3_600, &WagePeriod::Hour, &None, &None, &None),
+        Err(Ok(Error::NotAuthorized)),
+    );
+    assert_eq!(s.client.try_freeze_employee(&auditor, &employee), Err(Ok(Error::NotAuthorized)));
+    assert_eq!(s.client.try_deposit(&auditor, &s.token_id, &100), Err(Ok(Error::NotAuthorized)));
+    assert_eq!(s.client.try_withdraw_surplus(&auditor, &s.token_id, &1), Err(Ok(Error::NotAuthorized)));
+    assert_eq!(s.client.get_employee_info(&employee).wage_rate, 3_600);
+
+    // Role yang dicabut langsung kehilangan aksesnya
+    s.client.freeze_employee(&admin, &employee);
+    s.client.revoke_role(&admin, &Role::PayrollAdmin);
+    assert!(!s.client.has_role(&admin, &Role::PayrollAdmin));
+    assert_eq!(s.client.try_activate_employee(&admin, &employee), Err(Ok(Error::NotAuthorized)));
+}
+
+// Oracle dengan token payroll sebagai aset basis; 1 USD = `usd_price` / 10^7 token
+fn setup_oracle<'a>(env: &'a Env, s: &Setup, usd_price: i128, max_age: u64, max_dev_bps: u32) -> MockPriceOracleClient<'a> {
+    let oracle_id = env.register(MockPriceOracle, ());
+    let oracle = MockPriceOracleClient::new(env, &oracle_id);
+    oracle.initialize(&s.employer, &Asset::Stellar(s.token_id.clone()), &ORACLE_DECIMALS);
+    set_usd_price(env, &oracle, usd_price);
+    s.client.set_price_oracle(&s.employer, &oracle_id, &max_age, &max_dev_bps);
+    oracle
+}
+
+fn set_usd_price(env: &Env, oracle: &MockPriceOracleClient, price: i128) {
+    oracle.set_price(&Asset::Other(usd(env)), &price, &env.ledger().timestamp());
+}
+
+fn usd(env: &Env) -> Symbol {
+    Symbol::new(env, "USD")
+}
+
+// Employee bergaji `usd_per_hour` USD per jam
+fn hire_fiat(s: &Setup, env: &Env, usd_per_hour: i128) -> Address {
+    let employee = hire(s, env, 1, WagePeriod::Hour);
+    s.client.set_wage_currency(&s.employer, &employee, &Some(usd(env)), &usd_per_hour);
+    employee
+}
+
+#[test]
+fn test_fiat_wage_accrues_in_currency_and_pays_converted() {
+    let env = Env::default();
+    let s = setup(&env);
+    let oracle = setup_oracle(&env, &s, 20_000_000, 600, 0);
+    let employee = hire_fiat(&s, &env, 50);
+    fund_contract(&s, 10_000);
+
+    advance(&env, 3_600);
+    set_usd_price(&env, &oracle, 20_000_000);
+    // Saldo tetap dalam USD; pembayaran dikonversi dengan kurs 1 USD = 2 token
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 50);
+    s.client.withdraw(&employee, &50);
+    assert_eq!(s.token.balance(&employee), 100);
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 0);
+}
+
+#[test]
+fn test_fiat_withdraw_rejects_stale_price() {
+    let env = Env::default();
+    let s = setup(&env);
+    setup_oracle(&env, &s, 20_000_000, 600, 0);
+    let employee = hire_fiat(&s, &env, 50);
+    fund_contract(&s, 10_000);
+
+    // Harga terakhir sudah lebih tua dari max_age
+    advance(&env, 3_600);
+    assert_eq!(s.client.try_withdraw(&employee, &10), Err(Ok(Error::OraclePriceStale)));
+    assert_eq!(s.token.balance(&employee), 0);
+}
+
+#[test]
+fn test_fiat_withdraw_rejects_price_deviation_until_reset() {
+    let env = Env::default();
+    let s = setup(&env);
+    let oracle = setup_oracle(&env, &s, 20_000_000, 600, 1_000);
+    let employee = hire_fiat(&s, &env, 50);
+    fund_contract(&s, 10_000);
+
+    // Naik 50% dari kurs terakhir yang diterima, batasnya 10%
+    advance(&env, 3_600);
+    set_usd_price(&env, &oracle, 30_000_000);
+    assert_eq!(s.client.try_withdraw(&employee, &50), Err(Ok(Error::OraclePriceDeviation)));
+
+    s.client.reset_fx_rate(&s.employer, &usd(&env), &s.token_id);
+    s.client.withdraw(&employee, &50);
+    assert_eq!(s.token.balance(&employee), 150);
+}
+
+#[test]
+fn test_stale_price_does_not_block_token_payouts_with_runway_threshold() {
+    let env = Env::default();
+    let s = setup(&env);
+    let _oracle = setup_oracle(&env, &s, 20_000_000, 600, 0);
+    let _fiat = hire_fiat(&s, &env, 50);
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 100_000);
+    s.client.set_runway_threshold(&s.employer, &86_400);
+    s.client.set_severance_policy(&s.employer, &SeverancePolicy::Periods(1));
+    assert!(s.client.get_withdrawable_surplus(&s.token_id) > 0);
+
+    // Kurs USD basi: hanya employee fiat yang tertahan, gaji token dan pesangon tetap dibayar
+    advance(&env, 1_000);
+    s.client.withdraw(&employee, &500);
+    assert_eq!(s.token.balance(&employee), 500);
+    s.client.freeze_employee(&s.employer, &employee);
+    assert_eq!(s.token.balance(&employee), 1_000 + 3_600);
+}
+
+#[test]
+fn test_wind_down_claim_uses_snapshot_rate() {
+    let env = Env::default();
+    let s = setup(&env);
+    let oracle = setup_oracle(&env, &s, 20_000_000, 600, 0);
+    let employee = hire_fiat(&s, &env, 50);
+    fund_contract(&s, 10_000);
+    s.client.set_inactivity_window(&86_400);
+
+    advance(&env, 86_401);
+    set_usd_price(&env, &oracle, 20_000_000);
+    let wd = s.client.trigger_wind_down();
+    let pool = wd.pools.get(s.token_id.clone()).unwrap();
+    assert_eq!(pool.fx_rates.get(usd(&env)), Some(2_000_000_000));
+
+    // Kurs naik lalu oracle berhenti update; klaim tetap memakai kurs snapshot
+    set_usd_price(&env, &oracle, 40_000_000);
+    advance(&env, 3_600);
+    let paid = s.client.claim_wind_down_share(&employee);
+    assert_eq!(paid, 2_400);
+    assert_eq!(s.token.balance(&employee), 2_400);
+
+    // Sisa di atas bagian yang masih bisa diklaim kembali ke employer
+    let reclaimed = s.client.reclaim_wind_down_surplus(&s.token_id);
+    assert!(reclaimed >= 7_590);
+    assert_eq!(s.token.balance(&s.contract_id), 10_000 - 2_400 - reclaimed);
+    assert_eq!(s.token.balance(&s.employer), reclaimed);
+}
+
+#[test]
+fn test_wind_down_pays_every_claim_in_full_when_balance_covers_exact_owed() {
+    let env = Env::default();
+    let s = setup(&env);
+    let working = hire(&s, &env, 3_600, WagePeriod::Hour);
+    let on_leave = hire(&s, &env, 3_600, WagePeriod::Hour);
+    s.client.schedule_leave(&s.employer, &on_leave, &LeaveType::Unpaid, &START, &(START + 5_000));
+    fund_contract(&s, 1_000);
+    s.client.set_inactivity_window(&1_000);
+
+    // Agregat masih menghitung employee yang cuti unpaid; snapshot harus memakai kewajiban nyata
+    advance(&env, 1_001);
+    let wd = s.client.trigger_wind_down();
+    let pool = wd.pools.get(s.token_id.clone()).unwrap();
+    assert_eq!(pool.total_owed, 1_000);
+    assert_eq!(pool.balance, 1_000);
+
+    assert_eq!(s.client.claim_wind_down_share(&working), 1_000);
+    assert_eq!(s.token.balance(&working), 1_000);
+    assert_eq!(
+        s.client.try_claim_wind_down_share(&on_leave),
+        Err(Ok(Error::NothingToWithdraw)),
+    );
+}
+
+#[test]
+fn test_activate_before_start_does_not_accrue_early() {
+    let env = Env::default();
+    let s = setup(&env);
     let employee = Address::generate(&env);
-    let (token_id, token_client) = register_token_contract(&env, &employer);
+    s.client.add_employee(&s.employer, &employee, &3_600, &WagePeriod::Hour, &Some(START + 1_000), &None, &None);
+    fund_contract(&s, 10_000);
+
+    advance(&env, 100);
+    s.client.freeze_employee(&s.employer, &employee);
+    advance(&env, 100);
+    s.client.activate_employee(&s.employer, &employee);
+    let info = s.client.get_employee_info(&employee);
+    assert_eq!(info.last_accrual_timestamp, START + 1_000);
+    assert_eq!(info.total_suspended_seconds, 0);
+
+    advance(&env, 1_300);
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 500);
+}
+
+#[test]
+fn test_schedule_wage_rate_rejects_step_above_later_schedule() {
+    let env = Env::default();
+    let s = setup(&env);
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    s.client.schedule_wage_rate(&s.employer, &employee, &4_000, &(START + 2_000));
+
+    // Kenaikan ke 5_000 lebih dulu akan membuat jadwal 4_000 menjadi potongan gaji
+    assert_eq!(
+        s.client.try_schedule_wage_rate(&s.employer, &employee, &5_000, &(START + 1_000)),
+        Err(Ok(Error::InvalidRateChange)),
+    );
+    s.client.schedule_wage_rate(&s.employer, &employee, &3_800, &(START + 1_000));
+    assert_eq!(s.client.get_employee_info(&employee).scheduled_rates.len(), 2);
+}
+
+// --- Akurasi penarikan parsial ---
+
+#[test]
+fn test_partial_withdrawals_across_calendar_months_match_single_settle() {
+    let env = Env::default();
+    let s = setup(&env);
+    let sipping = hire(&s, &env, 1_000_003, WagePeriod::CalendarMonth);
+    let untouched = hire(&s, &env, 1_000_003, WagePeriod::CalendarMonth);
+    fund_contract(&s, 10_000_000);
+
+    // ~80 hari dengan langkah ganjil, melewati beberapa batas bulan kalender
+    let mut withdrawn: i128 = 0;
+    for step in 0..250 {
+        advance(&env, 27_433);
+        let available = s.client.get_live_accrued_balance(&sipping);
+        let amount = available / 3 + (step % 2);
+        if amount > 0 && amount <= available {
+            s.client.withdraw(&sipping, &amount);
+            withdrawn += amount;
+        }
+    }
+
+    assert_eq!(s.token.balance(&sipping), withdrawn);
+    assert_eq!(
+        withdrawn + s.client.get_live_accrued_balance(&sipping),
+        s.client.get_live_accrued_balance(&untouched),
+    );
+}
+
+// --- Insolvensi: distribusi pro-rata dan utang gaji ---
+
+// Dua employee (1 dan 2 token per detik) dengan saldo hanya separuh dari gaji terutang
+fn setup_insolvent(env: &Env) -> (Setup<'_>, Address, Address) {
+    let s = setup(env);
+    let small = hire(&s, env, 3_600, WagePeriod::Hour);
+    let large = hire(&s, env, 7_200, WagePeriod::Hour);
+    fund_contract(&s, 1_500);
+    advance(env, 1_000);
+    (s, small, large)
+}
+
+#[test]
+fn test_pro_rata_split_records_wage_debt() {
+    let env = Env::default();
+    let (s, small, large) = setup_insolvent(&env);
+
+    let result = s.client.distribute_pro_rata(&s.employer, &s.token_id);
+    assert_eq!(result.paid_count, 2);
+    assert_eq!(result.total_amount, 1_500);
+    assert_eq!(s.token.balance(&small), 500);
+    assert_eq!(s.token.balance(&large), 1_000);
+    assert_eq!(s.client.get_wage_debt(&small), 500);
+    assert_eq!(s.client.get_wage_debt(&large), 1_000);
+    assert_eq!(s.client.get_total_wage_debt(&s.token_id), 1_500);
+    assert_eq!(s.client.get_live_accrued_balance(&small), 0);
+
+    // Selama masih ada utang, penarikan biasa ditahan supaya tidak menyalip antrean
+    advance(&env, 10);
+    assert_eq!(s.client.try_withdraw(&small, &5), Err(Ok(Error::OutstandingWageDebt)));
+}
+
+#[test]
+fn test_deposit_settles_wage_debt_first() {
+    let env = Env::default();
+    let (s, small, large) = setup_insolvent(&env);
+    s.client.distribute_pro_rata(&s.employer, &s.token_id);
+
+    // Deposit parsial: utang dilunasi pro-rata sesuai porsinya
+    fund_contract(&s, 600);
+    assert_eq!(s.token.balance(&small), 700);
+    assert_eq!(s.token.balance(&large), 1_400);
+    assert_eq!(s.client.get_total_wage_debt(&s.token_id), 900);
+
+    fund_contract(&s, 2_000);
+    assert_eq!(s.token.balance(&small), 1_000);
+    assert_eq!(s.token.balance(&large), 2_000);
+    assert_eq!(s.client.get_total_wage_debt(&s.token_id), 0);
+    assert_eq!(s.client.get_wage_debt(&small), 0);
+    assert_eq!(s.token.balance(&s.contract_id), 1_100);
+}
+
+#[test]
+fn test_pro_rata_grace_period_for_unprivileged_callers() {
+    let env = Env::default();
+    let (s, small, _large) = setup_insolvent(&env);
+    let outsider = Address::generate(&env);
+
+    // Tanpa shortfall tercatat, pihak luar tidak bisa memicu distribusi
+    assert_eq!(
+        s.client.try_distribute_pro_rata(&outsider, &s.token_id),
+        Err(Ok(Error::InsolvencyGracePeriod)),
+    );
+    assert_eq!(s.client.report_shortfall(&s.token_id), Some(START + 1_000));
+    advance(&env, 3 * 86_400 - 1);
+    assert_eq!(
+        s.client.try_distribute_pro_rata(&outsider, &s.token_id),
+        Err(Ok(Error::InsolvencyGracePeriod)),
+    );
+
+    advance(&env, 1);
+    let result = s.client.distribute_pro_rata(&outsider, &s.token_id);
+    assert_eq!(result.total_amount, 1_500);
+    assert!(s.token.balance(&small) > 0);
+}
+
+#[test]
+fn test_insolvency_paths_leave_restricted_funds_alone() {
+    let env = Env::default();
+    let (s, small, large) = setup_insolvent(&env);
+    let funder = Address::generate(&env);
+    let restriction = FundingRestriction { employees: vec![&env, small.clone()], cost_centres: vec![&env] };
+    s.client.set_funding_restriction(&s.employer, &funder, &Some(restriction));
+    s.minter.mint(&funder, &600);
+    s.client.fund(&funder, &s.token_id, &600, &None);
+
+    // Hanya saldo umum yang dibagi; dana funder tetap tersisih
+    let result = s.client.distribute_pro_rata(&s.employer, &s.token_id);
+    assert_eq!(result.total_amount, 1_500);
+    assert_eq!(s.token.balance(&small), 500);
+    assert_eq!(s.token.balance(&large), 1_000);
+    assert_eq!(s.client.get_restricted_balance(&funder, &s.token_id), 600);
+    assert_eq!(s.token.balance(&s.contract_id), 600);
+
+    // Wind-down mengembalikan dana terbatas ke funder sebelum saldo pool di-snapshot
+    s.client.set_inactivity_window(&100);
+    advance(&env, 101);
+    let wd = s.client.trigger_wind_down();
+    assert_eq!(wd.pools.get(s.token_id.clone()).unwrap().balance, 0);
+    assert_eq!(s.token.balance(&funder), 600);
+    assert_eq!(s.client.get_restricted_balance(&funder, &s.token_id), 0);
+}
+
+#[test]
+fn test_final_pay_joins_wage_debt_queue_while_insolvent() {
+    let env = Env::default();
+    let (s, small, large) = setup_insolvent(&env);
+    s.client.distribute_pro_rata(&s.employer, &s.token_id);
+
+    // Freeze saat masih ada utang: final pay dicatat sebagai utang, bukan dibayar duluan
+    advance(&env, 100);
+    s.client.freeze_employee(&s.employer, &small);
+    assert_eq!(s.token.balance(&small), 500);
+    assert_eq!(s.client.get_wage_debt(&small), 600);
+    assert_eq!(s.client.get_total_wage_debt(&s.token_id), 1_600);
+
+    fund_contract(&s, 1_600);
+    assert_eq!(s.token.balance(&small), 1_100);
+    assert_eq!(s.token.balance(&large), 2_000);
+    assert_eq!(s.client.get_total_wage_debt(&s.token_id), 0);
+}
+
+// --- Multi-token ---
+
+#[test]
+fn test_multi_token_sweep_totals_and_disallow_with_frozen_employee() {
+    let env = Env::default();
+    let s = setup(&env);
+    let other_id = env.register_stellar_asset_contract_v2(s.employer.clone()).address();
+    s.client.allow_token(&s.employer, &other_id);
+    let in_main = hire(&s, &env, 3_600, WagePeriod::Hour);
+    let in_other = Address::generate(&env);
+    s.client.add_employee(&s.employer, &in_other, &7_200, &WagePeriod::Hour, &None, &None, &Some(other_id.clone()));
+    fund_contract(&s, 10_000);
+    StellarAssetClient::new(&env, &other_id).mint(&s.employer, &10_000);
+    s.client.deposit(&s.employer, &other_id, &10_000);
+
+    advance(&env, 100);
+    let result = s.client.payday_sweep_many(&s.employer, &vec![&env, in_main.clone(), in_other.clone()]);
+    assert_eq!(result.paid_count, 2);
+    assert_eq!(result.paid_by_token.get(s.token_id.clone()), Some(100));
+    assert_eq!(result.paid_by_token.get(other_id.clone()), Some(200));
+
+    // Employee beku dengan saldo nol masih memakai token itu
+    s.client.freeze_employee(&s.employer, &in_other);
+    assert_eq!(s.client.try_disallow_token(&s.employer, &other_id), Err(Ok(Error::TokenInUse)));
+    s.client.remove_employee(&s.employer, &in_other);
+    s.client.disallow_token(&s.employer, &other_id);
+}
 
-    // --- 1. Inisialisasi dan Tambah Karyawan ---
+// --- Migrasi ---
+
+#[test]
+fn test_migrate_rebuilds_legacy_layout_once() {
+    let env = Env::default();
     env.mock_all_auths();
-    client.initialize(&employer, &token_id);
-    client.add_employee(&employee, &100);
-    env.clear_all_auths();
+    env.ledger().set_timestamp(START);
+    let contract_id = env.register(FairWage, ());
+    let client = FairWageClient::new(&env, &contract_id);
+    let employer = Address::generate(&env);
+    let token_id = env.register_stellar_asset_contract_v2(employer.clone()).address();
+    let employee = Address::generate(&env);
 
-    // --- 2. Mint Token ke Majikan ---
-    env.as_contract(&token_id, || {
-        StellarAssetClient::new(&env, &token_id).mint(&employer, &10_000);
+    // Layout v1: wage_period berupa kode u32, tanpa SchemaVersion dan AllowedTokens
+    env.as_contract(&contract_id, || {
+        env.storage().instance().set(&DataKey::Employer, &employer);
+        env.storage().instance().set(&DataKey::Token, &token_id);
+        let mut raw: Map<Symbol, Val> = Map::new(&env);
+        raw.set(Symbol::new(&env, "wage_rate"), 86_400_i128.into_val(&env));
+        raw.set(Symbol::new(&env, "wage_period"), 1_u32.into_val(&env));
+        raw.set(Symbol::new(&env, "last_accrual_timestamp"), START.into_val(&env));
+        raw.set(Symbol::new(&env, "accrued_balance"), 50_i128.into_val(&env));
+        raw.set(Symbol::new(&env, "active"), true.into_val(&env));
+        env.storage().persistent().set(&DataKey::Employee(employee.clone()), &raw);
+        env.storage().persistent().set(&DataKey::EmployeeList, &vec![&env, employee.clone()]);
     });
-    assert_eq!(token_client.balance(&employer), 10_000);
-
-    // --- 3. Majikan Melakukan Deposit ---
-    let deposit_amount = 5_000_i128;
-    // Otorisasi untuk panggilan `transfer` yang terjadi di dalam `deposit`
-    let transfer_auth = AuthorizedInvocation {
-        function: AuthorizedFunction::Contract(
-            token_id.clone(),
-            Symbol::new(&env, "transfer"),
-            (&employer, &contract_id, &deposit_amount).into_val(&env),
-        ),
-        sub_invokes: Vec::new(&env),
-    };
-    // Otorisasi untuk panggilan `deposit` itu sendiri
-    let deposit_auth = AuthorizedInvocation {
-        function: AuthorizedFunction::Contract(
-            contract_id.clone(),
-            Symbol::new(&env, "deposit"),
-            (deposit_amount,).into_val(&env),
-        ),
-        sub_invokes: Vec::from_array(&env, &[transfer_auth]),
-    };
-    // Buat mock auth dan panggil
-    let mut auth = MockAuth::new(&env, &employer, vec![deposit_auth]);
-    auth.invoke_contract(&contract_id, &client.address, &Symbol::new(&env, "deposit"), (deposit_amount,).into_val(&env));
-    
-    assert_eq!(token_client.balance(&contract_id), 5_000);
-    assert_eq!(token_client.balance(&employer), 5_000);
 
-    // --- 4. Majukan Waktu dan Cek Saldo Akrual ---
-    env.ledger().set(LedgerInfo {
-        timestamp: env.ledger().get().timestamp + 10,
-        ..env.ledger().get()
-    });
-    assert_eq!(client.get_accrued_balance(&employee), 1000);
+    assert_eq!(client.migrate(), 1);
+    assert_eq!(client.get_schema_version(), 10);
+    let info = client.get_employee_info(&employee);
+    assert_eq!(info.wage_period, WagePeriod::Day);
+    assert_eq!(info.token, token_id);
+    assert_eq!(info.start_at, START);
+    assert_eq!(client.get_allowed_tokens(), vec![&env, token_id.clone()]);
+    let segments = client.get_rate_segments(&employee);
+    assert_eq!(segments.past.len(), 1);
+    assert_eq!(segments.past.get(0).unwrap().wage_rate, 86_400);
 
-    // --- 5. Karyawan Menarik Gaji ---
-    let withdraw_amount = 700_i128;
-    // Otorisasi untuk panggilan `transfer` yang terjadi di dalam `withdraw`
-    let withdraw_transfer_auth = AuthorizedInvocation {
-        function: AuthorizedFunction::Contract(
-            token_id.clone(),
-            Symbol::new(&env, "transfer"),
-            (&contract_id, &employee, &withdraw_amount).into_val(&env),
-        ),
-        sub_invokes: Vec::new(&env),
-    };
-    // Otorisasi untuk panggilan `withdraw` itu sendiri
-    let withdraw_auth = AuthorizedInvocation {
-        function: AuthorizedFunction::Contract(
-            contract_id.clone(),
-            Symbol::new(&env, "withdraw"),
-            (&employee, withdraw_amount).into_val(&env),
-        ),
-        sub_invokes: Vec::from_array(&env, &[withdraw_transfer_auth]),
-    };
-    // Buat mock auth dan panggil
-    let mut auth_withdraw = MockAuth::new(&env, &employee, vec![withdraw_auth]);
-    auth_withdraw.invoke_contract(&contract_id, &client.address, &Symbol::new(&env, "withdraw"), (&employee, withdraw_amount).into_val(&env));
-
-    assert_eq!(token_client.balance(&employee), 700);
-    assert_eq!(token_client.balance(&contract_id), 4_300);
+    advance(&env, 100);
+    assert_eq!(client.get_live_accrued_balance(&employee), 150);
+    assert_eq!(client.try_migrate(), Err(Ok(Error::AlreadyMigrated)));
+}
+
+// --- Cuti ---
+
+// Satu detik cuti per detik masa kerja supaya angkanya mudah dibaca
+fn set_leave_policy(s: &Setup) {
+    let policy = LeavePolicy { vacation_seconds_per_year: YEAR_SECONDS, sick_seconds_per_year: YEAR_SECONDS / 2 };
+    s.client.set_leave_policy(&s.employer, &policy);
+}
+
+#[test]
+fn test_leave_balance_excludes_frozen_time() {
+    let env = Env::default();
+    let s = setup(&env);
+    set_leave_policy(&s);
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 10_000);
+
+    advance(&env, 1_000);
+    assert_eq!(s.client.get_leave_balance(&employee).vacation_seconds, 1_000);
+    assert_eq!(s.client.get_leave_balance(&employee).sick_seconds, 500);
+    s.client.freeze_employee(&s.employer, &employee);
+    advance(&env, 5_000);
+    assert_eq!(s.client.get_leave_balance(&employee).vacation_seconds, 1_000);
+
+    s.client.activate_employee(&s.employer, &employee);
+    advance(&env, 200);
+    assert_eq!(s.client.get_leave_balance(&employee).vacation_seconds, 1_200);
+}
+
+#[test]
+fn test_paid_leave_deducts_balance_and_cancel_restores_it() {
+    let env = Env::default();
+    let s = setup(&env);
+    set_leave_policy(&s);
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 10_000);
+    advance(&env, 1_000);
+
+    let start = START + 2_000;
+    assert_eq!(
+        s.client.try_schedule_leave(&s.employer, &employee, &LeaveType::Sick, &start, &(start + 600)),
+        Err(Ok(Error::InsufficientLeaveBalance)),
+    );
+    s.client.schedule_leave(&s.employer, &employee, &LeaveType::Vacation, &start, &(start + 600));
+    assert_eq!(s.client.get_leave_balance(&employee).vacation_seconds, 400);
+    assert_eq!(
+        s.client.try_schedule_leave(&s.employer, &employee, &LeaveType::Vacation, &(start + 300), &(start + 700)),
+        Err(Ok(Error::InvalidLeaveWindow)),
+    );
+
+    s.client.cancel_leave(&s.employer, &employee, &start);
+    assert_eq!(s.client.get_leave_balance(&employee).vacation_seconds, 1_000);
+    assert_eq!(s.client.try_cancel_leave(&s.employer, &employee, &start), Err(Ok(Error::LeaveNotFound)));
+
+    // Cuti berbayar tetap di-accrue
+    s.client.schedule_leave(&s.employer, &employee, &LeaveType::Vacation, &start, &(start + 600));
+    advance(&env, 2_000);
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 3_000);
+}
+
+#[test]
+fn test_unpaid_leave_stops_accrual_and_corrects_liabilities() {
+    let env = Env::default();
+    let s = setup(&env);
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 10_000);
+    s.client.schedule_leave(&s.employer, &employee, &LeaveType::Unpaid, &(START + 100), &(START + 400));
+    assert_eq!(s.client.get_leave_balance(&employee).unpaid_seconds_taken, 300);
+
+    advance(&env, 1_000);
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 700);
+
+    // Begitu employee disentuh, kelebihan agregat selama cuti dikoreksi
+    s.client.withdraw(&employee, &100);
+    assert_eq!(s.client.get_total_liabilities(&s.token_id), 600);
+    assert_eq!(s.client.get_withdrawable_surplus(&s.token_id), 10_000 - 100 - 600);
+
+    // Membatalkan cuti unpaid yang belum mulai mengembalikan accrual penuh
+    s.client.schedule_leave(&s.employer, &employee, &LeaveType::Unpaid, &(START + 2_000), &(START + 2_500));
+    s.client.cancel_leave(&s.employer, &employee, &(START + 2_000));
+    advance(&env, 2_000);
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 2_600);
+    assert_eq!(s.client.get_total_liabilities(&s.token_id), 2_600);
+}
+
+// --- Surplus ---
+
+#[test]
+fn test_withdraw_surplus_keeps_owed_wages_in_contract() {
+    let env = Env::default();
+    let s = setup(&env);
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 5_000);
+    let employer_before = s.token.balance(&s.employer);
+
+    advance(&env, 1_000);
+    assert_eq!(s.client.get_withdrawable_surplus(&s.token_id), 4_000);
+    assert_eq!(
+        s.client.try_withdraw_surplus(&s.employer, &s.token_id, &4_001),
+        Err(Ok(Error::SurplusWouldUnderfundWages)),
+    );
+    s.client.withdraw_surplus(&s.employer, &s.token_id, &4_000);
+    assert_eq!(s.token.balance(&s.employer), employer_before + 4_000);
+    assert_eq!(s.client.get_withdrawable_surplus(&s.token_id), 0);
+
+    // Gaji yang terutang tetap bisa ditarik penuh
+    s.client.withdraw(&employee, &1_000);
+    assert_eq!(s.token.balance(&employee), 1_000);
+}
+
+// --- Close-out ---
+
+#[test]
+fn test_close_payroll_pays_out_refunds_funders_and_locks() {
+    let env = Env::default();
+    let s = setup(&env);
+    let other_id = env.register_stellar_asset_contract_v2(s.employer.clone()).address();
+    let other = TokenClient::new(&env, &other_id);
+    s.client.allow_token(&s.employer, &other_id);
+    let in_main = hire(&s, &env, 3_600, WagePeriod::Hour);
+    let in_other = Address::generate(&env);
+    s.client.add_employee(&s.employer, &in_other, &7_200, &WagePeriod::Hour, &None, &None, &Some(other_id.clone()));
+    fund_contract(&s, 5_000);
+    StellarAssetClient::new(&env, &other_id).mint(&s.employer, &5_000);
+    s.client.deposit(&s.employer, &other_id, &5_000);
+
+    let funder = Address::generate(&env);
+    let restriction = FundingRestriction { employees: vec![&env, in_main.clone()], cost_centres: vec![&env] };
+    s.client.set_funding_restriction(&s.employer, &funder, &Some(restriction));
+    s.minter.mint(&funder, &3_000);
+    s.client.fund(&funder, &s.token_id, &3_000, &None);
+
+    advance(&env, 1_000);
+    let summary = s.client.close_payroll();
+    assert_eq!(summary.paid_count, 2);
+    assert_eq!(summary.total_paid.get(s.token_id.clone()), Some(1_000));
+    assert_eq!(summary.total_paid.get(other_id.clone()), Some(2_000));
+    assert_eq!(s.token.balance(&in_main), 1_000);
+    assert_eq!(other.balance(&in_other), 2_000);
+
+    // Final pay memakai dana funder lebih dulu; sisanya kembali ke funder, bukan ke employer
+    assert_eq!(s.token.balance(&funder), 2_000);
+    assert_eq!(summary.surplus_returned.get(s.token_id.clone()), Some(5_000));
+    assert_eq!(summary.surplus_returned.get(other_id.clone()), Some(3_000));
+    assert_eq!(s.token.balance(&s.employer), 5_000);
+    assert_eq!(other.balance(&s.employer), 3_000);
+    assert_eq!(s.token.balance(&s.contract_id), 0);
+    assert_eq!(other.balance(&s.contract_id), 0);
+
+    s.minter.mint(&s.employer, &100);
+    assert_eq!(s.client.try_deposit(&s.employer, &s.token_id, &100), Err(Ok(Error::PayrollClosed)));
+    let late = Address::generate(&env);
+    assert_eq!(
+        s.client.try_add_employee(&s.employer, &late, &3_600, &WagePeriod::Hour, &None, &None, &None),
+        Err(Ok(Error::PayrollClosed)),
+    );
+    assert_eq!(s.client.try_close_payroll(), Err(Ok(Error::PayrollClosed)));
+}
+
+// --- Pause ---
+
+#[test]
+fn test_pause_blocks_outflows_while_accrual_continues() {
+    let env = Env::default();
+    let s = setup(&env);
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 10_000);
+    s.minter.mint(&s.employer, &100);
+    s.client.pause();
+
+    advance(&env, 1_000);
+    assert_eq!(s.client.try_withdraw(&employee, &100), Err(Ok(Error::Paused)));
+    assert_eq!(s.client.try_pay_partial_by_employer(&s.employer, &employee, &100), Err(Ok(Error::Paused)));
+    assert_eq!(s.client.try_payday_sweep(&s.employer, &employee), Err(Ok(Error::Paused)));
+    assert_eq!(s.client.try_payday_sweep_many(&s.employer, &vec![&env, employee.clone()]), Err(Ok(Error::Paused)));
+    assert_eq!(s.client.try_withdraw_surplus(&s.employer, &s.token_id, &100), Err(Ok(Error::Paused)));
+    assert_eq!(s.client.try_deposit(&s.employer, &s.token_id, &100), Err(Ok(Error::Paused)));
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 1_000);
+
+    s.client.unpause();
+    s.client.withdraw(&employee, &1_000);
+    assert_eq!(s.token.balance(&employee), 1_000);
+}
+
+// --- Resign dan pesangon ---
+
+#[test]
+fn test_resignation_stops_accrual_at_notice_end() {
+    let env = Env::default();
+    let s = setup(&env);
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 10_000);
+    s.client.set_notice_period(&s.employer, &1_000);
+
+    advance(&env, 100);
+    assert_eq!(s.client.resign(&employee), START + 1_100);
+    assert_eq!(s.client.try_resign(&employee), Err(Ok(Error::AlreadyResigned)));
+    advance(&env, 5_000);
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 1_100);
+    s.client.withdraw(&employee, &1_100);
+    assert_eq!(s.token.balance(&employee), 1_100);
+    assert_eq!(s.client.get_total_liabilities(&s.token_id), 0);
+}
+
+#[test]
+fn test_waive_notice_pays_out_immediately() {
+    let env = Env::default();
+    let s = setup(&env);
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 10_000);
+    s.client.set_notice_period(&s.employer, &1_000);
+
+    advance(&env, 100);
+    assert_eq!(s.client.try_waive_notice(&s.employer, &employee), Err(Ok(Error::NotResigned)));
+    s.client.resign(&employee);
+    advance(&env, 200);
+    s.client.waive_notice(&s.employer, &employee);
+    assert_eq!(s.token.balance(&employee), 300);
+    assert_eq!(s.client.get_resignation(&employee), Some(START + 300));
+
+    advance(&env, 1_000);
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 0);
+    assert_eq!(s.client.get_total_liabilities(&s.token_id), 0);
+}
+
+#[test]
+fn test_severance_paid_once_from_surplus() {
+    let env = Env::default();
+    let s = setup(&env);
+    s.client.set_severance_policy(&s.employer, &SeverancePolicy::Periods(2));
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 100_000);
+
+    advance(&env, 1_000);
+    s.client.freeze_employee(&s.employer, &employee);
+    assert_eq!(s.token.balance(&employee), 1_000 + 7_200);
+
+    // Freeze berikutnya hanya membayar gaji, pesangon tidak dibayar dua kali
+    s.client.activate_employee(&s.employer, &employee);
+    advance(&env, 100);
+    s.client.freeze_employee(&s.employer, &employee);
+    assert_eq!(s.token.balance(&employee), 1_100 + 7_200);
+    assert_eq!(s.client.get_severance_due(&employee), 0);
+}
+
+#[test]
+fn test_severance_refused_when_treasury_cannot_cover_it() {
+    let env = Env::default();
+    let s = setup(&env);
+    s.client.set_severance_policy(&s.employer, &SeverancePolicy::Periods(2));
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 1_500);
+
+    advance(&env, 1_000);
+    assert_eq!(
+        s.client.try_freeze_employee(&s.employer, &employee),
+        Err(Ok(Error::InsufficientContractBalance)),
+    );
+    assert!(s.client.get_employee_info(&employee).active);
+    assert_eq!(s.token.balance(&employee), 0);
+}
+
+#[test]
+fn test_severance_skipped_after_resignation_or_ended_term() {
+    let env = Env::default();
+    let s = setup(&env);
+    s.client.set_severance_policy(&s.employer, &SeverancePolicy::Periods(2));
+    let resigned = hire(&s, &env, 3_600, WagePeriod::Hour);
+    let fixed_term = Address::generate(&env);
+    s.client.add_employee(&s.employer, &fixed_term, &3_600, &WagePeriod::Hour, &None, &Some(START + 500), &None);
+    fund_contract(&s, 100_000);
+
+    advance(&env, 100);
+    s.client.resign(&resigned);
+    advance(&env, 900);
+    s.client.freeze_employee(&s.employer, &resigned);
+    assert_eq!(s.token.balance(&resigned), 1_000);
+
+    s.client.freeze_employee(&s.employer, &fixed_term);
+    assert_eq!(s.token.balance(&fixed_term), 500);
+    assert_eq!(s.client.get_total_liabilities(&s.token_id), 0);
+}
+
+// --- Migrasi token ---
+
+const MIGRATION_DELAY: u64 = 7 * 86_400;
+
+#[test]
+fn test_token_migration_without_rate_requires_settled_balances() {
+    let env = Env::default();
+    let s = setup(&env);
+    let new_id = env.register_stellar_asset_contract_v2(s.employer.clone()).address();
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 1_000_000);
+
+    s.client.migrate_token(&new_id, &None);
+    assert_eq!(s.client.try_execute_token_migration(), Err(Ok(Error::TokenMigrationTimelocked)));
+    advance(&env, MIGRATION_DELAY);
+    assert_eq!(s.client.try_execute_token_migration(), Err(Ok(Error::UnsettledBalance)));
+
+    s.client.withdraw(&employee, &s.client.get_live_accrued_balance(&employee));
+    assert_eq!(s.client.execute_token_migration(), 1);
+    assert_eq!(s.client.get_employee_info(&employee).token, new_id);
+    assert_eq!(s.client.get_allowed_tokens(), vec![&env, new_id.clone()]);
+    assert_eq!(s.client.get_token_migration(), None);
+    assert_eq!(
+        s.client.try_deposit(&s.employer, &s.token_id, &100),
+        Err(Ok(Error::TokenNotAllowed)),
+    );
+}
+
+#[test]
+fn test_token_migration_converts_balance_and_remainder() {
+    let env = Env::default();
+    let s = setup(&env);
+    let new_id = env.register_stellar_asset_contract_v2(s.employer.clone()).address();
+    let employee = hire(&s, &env, 3_601, WagePeriod::Hour);
+
+    // 2 token baru per token lama
+    s.client.migrate_token(&new_id, &Some(2_000_000_000));
+    advance(&env, MIGRATION_DELAY + 1);
+    assert_eq!(s.client.execute_token_migration(), 1);
+
+    // 3_601 per jam selama 604_801 detik = 604_969 unit + 1/3_600; dikali dua jadi 1_209_938 + 2/3_600
+    let info = s.client.get_employee_info(&employee);
+    assert_eq!(info.token, new_id);
+    assert_eq!(info.wage_rate, 7_202);
+    assert_eq!(info.accrued_balance, 1_209_938);
+    assert_eq!(info.accrual_remainder, 2);
+
+    // Sisa pecahan ikut terbawa: (2 + 7_202 * 1_799) / 3_600 tepat 3_599
+    advance(&env, 1_799);
+    assert_eq!(s.client.get_live_accrued_balance(&employee), 1_209_938 + 3_599);
+    assert_eq!(s.client.get_total_liabilities(&new_id), 1_209_938 + 3_599);
+    assert_eq!(s.client.get_total_liabilities(&s.token_id), 0);
+}
+
+// --- Auto top-up ---
+
+#[test]
+fn test_auto_topup_pulls_shortfall_within_cap_and_allowance() {
+    let env = Env::default();
+    let s = setup(&env);
+    let employee = hire(&s, &env, 3_600, WagePeriod::Hour);
+    fund_contract(&s, 100);
+    s.minter.mint(&s.employer, &10_000);
+    s.token.approve(&s.employer, &s.contract_id, &1_000, &1_000);
+    s.client.set_auto_topup(&s.token_id, &400, &3_600);
+
+    // Hanya kekurangannya yang ditarik lewat transfer_from
+    advance(&env, 300);
+    s.client.withdraw(&employee, &300);
+    assert_eq!(s.token.balance(&s.employer), 10_000 - 200);
+    assert_eq!(s.token.balance(&s.contract_id), 0);
+    assert_eq!(s.client.get_auto_topup(&s.token_id).unwrap().pulled_in_window, 200);
+
+    // Batas per periode
+    advance(&env, 300);
+    assert_eq!(s.client.try_withdraw(&employee, &300), Err(Ok(Error::InsufficientContractBalance)));
+    s.client.withdraw(&employee, &200);
+    assert_eq!(s.client.get_auto_topup(&s.token_id).unwrap().pulled_in_window, 400);
+
+    // Window baru mereset jumlah yang sudah ditarik
+    advance(&env, 3_000);
+    s.client.withdraw(&employee, &300);
+    let topup = s.client.get_auto_topup(&s.token_id).unwrap();
+    assert_eq!(topup.window_start, START + 3_600);
+    assert_eq!(topup.pulled_in_window, 300);
+
+    // Sisa allowance 300 tidak cukup untuk kekurangan 400
+    advance(&env, 3_600);
+    assert_eq!(s.token.allowance(&s.employer, &s.contract_id), 300);
+    assert_eq!(s.client.try_withdraw(&employee, &400), Err(Ok(Error::InsufficientContractBalance)));
+    assert_eq!(s.token.balance(&employee), 800);
+}
+
+// --- Pendanaan pihak ketiga ---
+
+fn restrict_to_cost_centre(env: &Env, s: &Setup, funder: &Address, centre: &str) {
+    let restriction = FundingRestriction { employees: vec![env], cost_centres: vec![env, Symbol::new(env, centre)] };
+    s.client.set_funding_restriction(&s.employer, funder, &Some(restriction));
+}
+
+#[test]
+fn test_restricted_funds_pay_only_allowed_employees() {
+    let env = Env::default();
+    let s = setup(&env);
+    let engineer = hire(&s, &env, 3_600, WagePeriod::Hour);
+    let other = hire(&s, &env, 3_600, WagePeriod::Hour);
+    s.client.set_cost_centre(&s.employer, &engineer, &Some(Symbol::new(&env, "ENG")));
+    let funder = Address::generate(&env);
+    restrict_to_cost_centre(&env, &s, &funder, "ENG");
+    s.minter.mint(&funder, &1_500);
+    s.client.fund(&funder, &s.token_id, &1_000, &Some(String::from_str(&env, "grant Q1")));
+    s.client.fund(&funder, &s.token_id, &500, &None);
+    assert_eq!(s.client.get_funder_total(&funder, &s.token_id), 1_500);
+    assert_eq!(s.client.get_restricted_balance(&funder, &s.token_id), 1_500);
+    assert_eq!(s.client.get_withdrawable_surplus(&s.token_id), 0);
+
+    advance(&env, 1_000);
+    assert_eq!(s.client.try_withdraw(&other, &1_000), Err(Ok(Error::InsufficientContractBalance)));
+    s.client.withdraw(&engineer, &1_000);
+    assert_eq!(s.client.get_restricted_balance(&funder, &s.token_id), 500);
+
+    // Melepas batasan menjadikan sisanya dana umum payroll
+    s.client.set_funding_restriction(&s.employer, &funder, &None);
+    assert_eq!(s.client.get_restricted_balance(&funder, &s.token_id), 0);
+    s.client.withdraw(&other, &500);
+    assert_eq!(s.token.balance(&other), 500);
+}
+
+#[test]
+fn test_restricted_funder_limits() {
+    let env = Env::default();
+    let s = setup(&env);
+    for _ in 0..8 {
+        let funder = Address::generate(&env);
+        restrict_to_cost_centre(&env, &s, &funder, "ENG");
+        s.minter.mint(&funder, &10);
+        s.client.fund(&funder, &s.token_id, &10, &None);
+    }
+    let ninth = Address::generate(&env);
+    restrict_to_cost_centre(&env, &s, &ninth, "ENG");
+    s.minter.mint(&ninth, &10);
+    assert_eq!(
+        s.client.try_fund(&ninth, &s.token_id, &10, &None),
+        Err(Ok(Error::TooManyRestrictedFunders)),
+    );
+
+    // Funder tanpa batasan tidak ikut dihitung
+    let open_funder = Address::generate(&env);
+    s.minter.mint(&open_funder, &10);
+    s.client.fund(&open_funder, &s.token_id, &10, &None);
+    assert_eq!(s.client.get_funder_total(&open_funder, &s.token_id), 10);
+    assert_eq!(s.client.get_restricted_balance(&open_funder, &s.token_id), 0);
+
+    let mut employees = vec![&env];
+    for _ in 0..17 {
+        employees.push_back(Address::generate(&env));
+    }
+    let too_large = FundingRestriction { employees, cost_centres: vec![&env] };
+    assert_eq!(
+        s.client.try_set_funding_restriction(&s.employer, &ninth, &Some(too_large)),
+        Err(Ok(Error::FundingRestrictionTooLarge)),
+    );
 }
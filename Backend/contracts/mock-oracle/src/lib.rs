@@ -0,0 +1,95 @@
+// src/lib.rs
+// Oracle harga tiruan dengan antarmuka gaya SEP-40 (`base`, `decimals`, `lastprice`) untuk
+// menguji gaji fiat di kontrak payroll. Harga diisi manual oleh admin lewat `set_price`.
+#![no_std]
+use soroban_sdk::{contract, contracterror, contractimpl, contracttype, Address, Env, Symbol, symbol_short};
+
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidPrice = 3,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    Base,
+    Decimals,
+    Price(Asset),
+}
+
+#[contract]
+pub struct MockPriceOracle;
+
+impl MockPriceOracle {
+    fn require_admin(env: &Env) -> Result<(), Error> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        admin.require_auth();
+        Ok(())
+    }
+}
+
+#[contractimpl]
+impl MockPriceOracle {
+    pub fn initialize(env: Env, admin: Address, base: Asset, decimals: u32) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Base, &base);
+        env.storage().instance().set(&DataKey::Decimals, &decimals);
+        Ok(())
+    }
+
+    // `timestamp` bebas diisi supaya test bisa mensimulasikan harga basi
+    pub fn set_price(env: Env, asset: Asset, price: i128, timestamp: u64) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+        if price <= 0 {
+            return Err(Error::InvalidPrice);
+        }
+        let data = PriceData { price, timestamp };
+        env.storage().persistent().set(&DataKey::Price(asset.clone()), &data);
+        env.events().publish((symbol_short!("price"),), (asset, price, timestamp));
+        Ok(())
+    }
+
+    pub fn remove_price(env: Env, asset: Asset) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+        env.storage().persistent().remove(&DataKey::Price(asset));
+        Ok(())
+    }
+
+    pub fn base(env: Env) -> Result<Asset, Error> {
+        env.storage().instance().get(&DataKey::Base).ok_or(Error::NotInitialized)
+    }
+
+    pub fn decimals(env: Env) -> Result<u32, Error> {
+        env.storage().instance().get(&DataKey::Decimals).ok_or(Error::NotInitialized)
+    }
+
+    pub fn lastprice(env: Env, asset: Asset) -> Option<PriceData> {
+        env.storage().persistent().get(&DataKey::Price(asset))
+    }
+}
@@ -373,7 +373,10 @@ app.post("/api/debug/test-freeze", async (req, res) => {
       const freezeOp = StellarSdk.Operation.invokeContractFunction({
         contract: fairWageContractId,
         function: "freeze_employee",
-        args: [StellarSdk.Address.fromString(employeeAddress).toScVal()],
+        args: [
+          StellarSdk.Address.fromString(userPublicKey).toScVal(),
+          StellarSdk.Address.fromString(employeeAddress).toScVal(),
+        ],
       });
 
       const freezeTx = new StellarSdk.TransactionBuilder(sourceAccount, {
@@ -917,6 +920,32 @@ app.get("/api/contract-info", async (req, res) => {
 // ================================
 // EMPLOYEE MANAGEMENT
 // ================================
+const WAGE_PERIODS = [
+  "Hour",
+  "Day",
+  "Week",
+  "Biweekly",
+  "SemiMonthly",
+  "Month",
+  "CalendarMonth",
+  "Year",
+];
+const LEGACY_WAGE_PERIODS = ["Hour", "Day", "Week", "Month"];
+
+// Enum unit Soroban di-encode sebagai vec berisi satu symbol nama varian
+function wagePeriodToScVal(wagePeriod) {
+  let name = "Day";
+  if (WAGE_PERIODS.includes(wagePeriod)) {
+    name = wagePeriod;
+  } else {
+    const parsed = parseInt(wagePeriod);
+    if (!Number.isNaN(parsed) && LEGACY_WAGE_PERIODS[parsed]) {
+      name = LEGACY_WAGE_PERIODS[parsed];
+    }
+  }
+  return StellarSdk.xdr.ScVal.scvVec([StellarSdk.xdr.ScVal.scvSymbol(name)]);
+}
+
 app.post("/api/add-employee", async (req, res) => {
   try {
     const {
@@ -952,18 +981,19 @@ app.post("/api/add-employee", async (req, res) => {
         });
     }
 
-    // wagePeriod normalization (0=Hour, 1=Day, 2=Week, 3=Month). Default = Day(1)
-    let wagePeriodInt = 1;
-    const parsed = parseInt(wagePeriod);
-    if (!Number.isNaN(parsed)) wagePeriodInt = parsed;
-    if (wagePeriodInt < 0 || wagePeriodInt > 3) wagePeriodInt = 1;
+    // wagePeriod: nama varian enum `WagePeriod` di kontrak, atau angka lama
+    // (0=Hour, 1=Day, 2=Week, 3=Month). Default = Day
+    const wagePeriodScVal = wagePeriodToScVal(wagePeriod);
 
     const sourceAccount = await horizonServer.loadAccount(userPublicKey);
 
+    // `add_employee` butuh tanda tangan employee juga; dari backend employer hanya membuat
+    // tawaran, lalu employee menerimanya lewat /api/accept-offer
     const op = StellarSdk.Operation.invokeContractFunction({
       contract: fairWageContractId,
-      function: "add_employee",
+      function: "offer_employment",
       args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
         StellarSdk.Address.fromString(employeeAddress).toScVal(),
         StellarSdk.xdr.ScVal.scvI128(
           new StellarSdk.xdr.Int128Parts({
@@ -971,7 +1001,10 @@ app.post("/api/add-employee", async (req, res) => {
             hi: StellarSdk.xdr.Int64.fromString("0"),
           }),
         ),
-        StellarSdk.xdr.ScVal.scvU32(wagePeriodInt),
+        wagePeriodScVal,
+        StellarSdk.xdr.ScVal.scvVoid(), // start_at: mulai saat offer diterima
+        StellarSdk.xdr.ScVal.scvVoid(), // end_at: tanpa batas waktu
+        StellarSdk.xdr.ScVal.scvVoid(), // terms_hash
       ],
     });
 
@@ -990,7 +1023,8 @@ app.post("/api/add-employee", async (req, res) => {
       transactionXdr: preparedTx.toXDR(),
       fairWageContractId,
       employeeAddress,
-      message: "Add employee transaction prepared - ready for signing",
+      message:
+        "Employment offer transaction prepared - ready for signing; the employee must accept it via /api/accept-offer",
     });
   } catch (error) {
     console.error("❌ Error adding employee:", error);
@@ -998,6 +1032,107 @@ app.post("/api/add-employee", async (req, res) => {
   }
 });
 
+// Employee menerima tawaran kerja; transaksi ditandatangani oleh employee sendiri
+app.post("/api/accept-offer", async (req, res) => {
+  try {
+    const { userPublicKey, fairWageContractId } = req.body;
+
+    if (!userPublicKey || !fairWageContractId) {
+      return res
+        .status(400)
+        .json({
+          success: false,
+          error: "Missing required parameters: userPublicKey, fairWageContractId",
+        });
+    }
+
+    const sourceAccount = await horizonServer.loadAccount(userPublicKey);
+
+    const op = StellarSdk.Operation.invokeContractFunction({
+      contract: fairWageContractId,
+      function: "accept_offer",
+      args: [StellarSdk.Address.fromString(userPublicKey).toScVal()],
+    });
+
+    const tx = new StellarSdk.TransactionBuilder(sourceAccount, {
+      fee: "100000",
+      networkPassphrase,
+    })
+      .addOperation(op)
+      .setTimeout(30)
+      .build();
+
+    const preparedTx = await server.prepareTransaction(tx);
+
+    res.json({
+      success: true,
+      transactionXdr: preparedTx.toXDR(),
+      fairWageContractId,
+      employeeAddress: userPublicKey,
+      message: "Accept offer transaction prepared - ready for signing",
+    });
+  } catch (error) {
+    console.error("❌ Error accepting offer:", error);
+    res.status(500).json({ success: false, error: error.message });
+  }
+});
+
+// Tawaran kerja yang masih menunggu persetujuan employee (null kalau tidak ada)
+app.post("/api/get-offer", async (req, res) => {
+  try {
+    const { fairWageContractId, employeeAddress } = req.body;
+
+    if (!fairWageContractId || !employeeAddress) {
+      return res
+        .status(400)
+        .json({
+          success: false,
+          error:
+            "Missing required parameters: fairWageContractId, employeeAddress",
+        });
+    }
+
+    const op = StellarSdk.Operation.invokeContractFunction({
+      contract: fairWageContractId,
+      function: "get_offer",
+      args: [StellarSdk.Address.fromString(employeeAddress).toScVal()],
+    });
+
+    const sourceAccount = await getSimulationAccount(req);
+    const tx = new StellarSdk.TransactionBuilder(sourceAccount, {
+      fee: "100000",
+      networkPassphrase,
+    })
+      .addOperation(op)
+      .setTimeout(30)
+      .build();
+
+    const simulation = await server.simulateTransaction(tx);
+    if (simulation.error)
+      throw new Error(`Simulation failed: ${simulation.error.message}`);
+
+    let offer = null;
+    if (simulation.result && simulation.result.retval) {
+      const native = StellarSdk.scValToNative(simulation.result.retval);
+      if (native) {
+        offer = {
+          wage_rate: native.wage_rate?.toString?.() ?? String(native.wage_rate || "0"),
+          wage_period: Array.isArray(native.wage_period)
+            ? native.wage_period[0]
+            : String(native.wage_period),
+          offered_at: Number(native.offered_at?.toString?.() ?? native.offered_at ?? 0),
+          expires_at: Number(native.expires_at?.toString?.() ?? native.expires_at ?? 0),
+        };
+      }
+    }
+
+    res.json({ success: true, offer });
+  } catch (error) {
+    console.error("❌ Error getting offer:", error);
+    res.status(500).json({ success: false, error: error.message });
+  }
+});
+
 app.post("/api/set-salary", async (req, res) => {
   try {
     const { userPublicKey, fairWageContractId, employeeAddress, wageRate } =
@@ -1087,6 +1222,7 @@ app.post("/api/update-wage-rate", async (req, res) => {
       contract: fairWageContractId,
       function: "update_wage_rate",
       args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
         StellarSdk.Address.fromString(employeeAddress).toScVal(),
         StellarSdk.xdr.ScVal.scvI128(
           new StellarSdk.xdr.Int128Parts({
@@ -1140,7 +1276,10 @@ app.post("/api/pay-employee", async (req, res) => {
     const op = StellarSdk.Operation.invokeContractFunction({
       contract: fairWageContractId,
       function: "payday_sweep",
-      args: [StellarSdk.Address.fromString(employeeAddress).toScVal()],
+      args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
+        StellarSdk.Address.fromString(employeeAddress).toScVal(),
+      ],
     });
 
     const tx = new StellarSdk.TransactionBuilder(sourceAccount, {
@@ -1250,7 +1389,10 @@ app.post("/api/remove-employee", async (req, res) => {
     const op = StellarSdk.Operation.invokeContractFunction({
       contract: fairWageContractId,
       function: "remove_employee",
-      args: [StellarSdk.Address.fromString(employeeAddress).toScVal()],
+      args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
+        StellarSdk.Address.fromString(employeeAddress).toScVal(),
+      ],
     });
 
     const tx = new StellarSdk.TransactionBuilder(sourceAccount, {
@@ -1302,7 +1444,10 @@ app.post("/api/pay-all-wages", async (req, res) => {
     const op = StellarSdk.Operation.invokeContractFunction({
       contract: fairWageContractId,
       function: "payday_sweep",
-      args: [StellarSdk.Address.fromString(employeeAddress).toScVal()],
+      args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
+        StellarSdk.Address.fromString(employeeAddress).toScVal(),
+      ],
     });
 
     const tx = new StellarSdk.TransactionBuilder(sourceAccount, {
@@ -1405,7 +1550,10 @@ app.post("/api/fix-employee-timestamp", async (req, res) => {
     const op = StellarSdk.Operation.invokeContractFunction({
       contract: fairWageContractId,
       function: "fix_employee_timestamp",
-      args: [StellarSdk.Address.fromString(employeeAddress).toScVal()],
+      args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
+        StellarSdk.Address.fromString(employeeAddress).toScVal(),
+      ],
     });
 
     const tx = new StellarSdk.TransactionBuilder(sourceAccount, {
@@ -1552,6 +1700,7 @@ app.post("/api/pay-partial-employer", async (req, res) => {
       contract: fairWageContractId,
       function: "pay_partial_by_employer",
       args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
         StellarSdk.Address.fromString(employeeAddress).toScVal(),
         StellarSdk.xdr.ScVal.scvI128(
           new StellarSdk.xdr.Int128Parts({
@@ -1616,7 +1765,10 @@ app.post("/api/payday-sweep-many", async (req, res) => {
     const op = StellarSdk.Operation.invokeContractFunction({
       contract: fairWageContractId,
       function: "payday_sweep_many",
-      args: [StellarSdk.xdr.ScVal.scvVec(addressArray)],
+      args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
+        StellarSdk.xdr.ScVal.scvVec(addressArray),
+      ],
     });
 
     const tx = new StellarSdk.TransactionBuilder(sourceAccount, {
@@ -1858,16 +2010,20 @@ app.post("/api/get-employee-info", async (req, res) => {
 app.post("/api/get-contract-balance", async (req, res) => {
   try {
     const { fairWageContractId } = req.body;
+    // Saldo kontrak sekarang per token; default ke token payroll yang sedang dipakai
+    const tokenContractId = req.body.tokenContractId || getTokenContractId();
 
-    if (!fairWageContractId)
+    if (!fairWageContractId || !tokenContractId)
       return res
         .status(400)
-        .json({ error: "Missing required parameters: fairWageContractId" });
+        .json({
+          error: "Missing required parameters: fairWageContractId, tokenContractId",
+        });
 
     const op = StellarSdk.Operation.invokeContractFunction({
       contract: fairWageContractId,
       function: "get_contract_balance",
-      args: [],
+      args: [StellarSdk.Address.fromString(tokenContractId).toScVal()],
     });
 
     const sourceAccount = await getSimulationAccount(req);
@@ -2017,7 +2173,10 @@ app.post("/api/freeze-employee", async (req, res) => {
     const op = StellarSdk.Operation.invokeContractFunction({
       contract: fairWageContractId,
       function: "freeze_employee",
-      args: [StellarSdk.Address.fromString(employeeAddress).toScVal()],
+      args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
+        StellarSdk.Address.fromString(employeeAddress).toScVal(),
+      ],
     });
     console.log("✅ Operation created");
 
@@ -2070,7 +2229,10 @@ app.post("/api/activate-employee", async (req, res) => {
     const op = StellarSdk.Operation.invokeContractFunction({
       contract: fairWageContractId,
       function: "activate_employee",
-      args: [StellarSdk.Address.fromString(employeeAddress).toScVal()],
+      args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
+        StellarSdk.Address.fromString(employeeAddress).toScVal(),
+      ],
     });
 
     const tx = new StellarSdk.TransactionBuilder(sourceAccount, {
@@ -2100,7 +2262,8 @@ app.post("/api/activate-employee", async (req, res) => {
   }
 });
 
-// Employee withdraw (employee memicu payday_sweep utk dirinya)
+// Employee withdraw (employee menarik gajinya sendiri lewat `withdraw`; payday_sweep
+// sekarang khusus employer/PayrollAdmin)
 app.post("/api/employee-withdraw", async (req, res) => {
   try {
     const { userPublicKey, fairWageContractId, amount } = req.body;
@@ -2115,12 +2278,26 @@ app.post("/api/employee-withdraw", async (req, res) => {
         });
     }
 
+    // Validate amount as positive number
+    const numAmount = parseFloat(amount);
+    if (isNaN(numAmount) || numAmount <= 0) {
+      return res
+        .status(400)
+        .json({ success: false, error: "Amount must be a valid positive number" });
+    }
+
+    // Convert to proper stroops (7 decimal places), same as /api/withdraw
+    const stroopsAmount = Math.floor(numAmount * 10000000);
+
     const sourceAccount = await horizonServer.loadAccount(userPublicKey);
 
     const op = StellarSdk.Operation.invokeContractFunction({
       contract: fairWageContractId,
-      function: "payday_sweep",
-      args: [StellarSdk.Address.fromString(userPublicKey).toScVal()],
+      function: "withdraw",
+      args: [
+        StellarSdk.Address.fromString(userPublicKey).toScVal(),
+        StellarSdk.nativeToScVal(stroopsAmount, { type: 'i128' })
+      ],
     });
 
     const tx = new StellarSdk.TransactionBuilder(sourceAccount, {
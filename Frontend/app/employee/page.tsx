@@ -6,6 +6,7 @@ import BalanceCard from '@/components/employee/BalanceCard';
 import WithdrawCard from '@/components/employee/WithdrawCard';
 import TransactionHistoryCard from '@/components/employee/TransactionHistoryCard';
 import CompanySelector from '@/components/employee/CompanySelector';
+import OfferCard from '@/components/employee/OfferCard';
 import { Button } from '@/components/ui/button';
 import { Wallet, Users, AlertCircle } from 'lucide-react';
 import { getCurrentContractId } from '@/lib/soroban';
@@ -137,6 +138,13 @@ export default function EmployeePage() {
           />
         </div>
 
+        {/* Pending offer - renders nothing once accepted */}
+        {selectedContract && (
+          <div className="mb-8">
+            <OfferCard selectedContract={selectedContract} />
+          </div>
+        )}
+
         {/* Main Dashboard Grid - Only show if contract selected */}
         {selectedContract && (
           <div className="grid lg:grid-cols-2 gap-6 mb-8">
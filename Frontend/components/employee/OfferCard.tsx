@@ -0,0 +1,101 @@
+"use client";
+
+import React, { useState, useEffect } from "react";
+import { FileSignature } from "lucide-react";
+import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
+import { getOffer, acceptOffer, EmploymentOffer } from "@/lib/soroban";
+import { useWallet } from "@/contexts/WalletContext";
+
+const formatBigintTokens = (bi: bigint) => {
+  const s = bi.toString();
+  if (s.length <= 7) return `0.${s.padStart(7, "0")} tokens`;
+  return `${s.slice(0, -7)}.${s.slice(-7)} tokens`;
+};
+
+interface Contract {
+  contractId: string;
+  companyName: string;
+  tokenSymbol: string;
+  tokenContract: string;
+}
+
+interface OfferCardProps {
+  selectedContract?: Contract;
+}
+
+// Shown only while the employer's offer is waiting for the employee to accept it
+const OfferCard: React.FC<OfferCardProps> = ({ selectedContract }) => {
+  const [offer, setOffer] = useState<EmploymentOffer | null>(null);
+  const [isLoading, setIsLoading] = useState(false);
+  const [errorMsg, setErrorMsg] = useState<string | null>(null);
+  const { publicKey, isWalletConnected } = useWallet();
+
+  const loadOffer = async () => {
+    if (!isWalletConnected || !publicKey || !selectedContract) return;
+    try {
+      setOffer(await getOffer(selectedContract.contractId, publicKey));
+    } catch (error) {
+      console.error('❌ Failed to load offer:', error);
+      setOffer(null);
+    }
+  };
+
+  useEffect(() => {
+    loadOffer();
+  }, [isWalletConnected, publicKey, selectedContract?.contractId]);
+
+  const handleAccept = async () => {
+    if (!selectedContract) return;
+    setIsLoading(true); setErrorMsg(null);
+    try {
+      await acceptOffer(selectedContract.contractId);
+      setOffer(null);
+      alert("Offer accepted! Your wages start accruing from now.");
+    } catch (error) {
+      console.error('❌ Failed to accept offer:', error);
+      setErrorMsg(error instanceof Error ? error.message : "Failed to accept offer");
+    } finally {
+      setIsLoading(false);
+    }
+  };
+
+  if (!offer) return null;
+
+  const expired = offer.expires_at > 0 && Date.now() / 1000 >= offer.expires_at;
+
+  return (
+    <Card className="bg-white border-amber-200 shadow-soft">
+      <CardHeader>
+        <CardTitle className="flex items-center text-slate-900">
+          <FileSignature className="w-5 h-5 mr-2 text-amber-600" />
+          Pending Employment Offer
+        </CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <p className="text-slate-600">
+          {selectedContract?.companyName || "Your employer"} offered you{" "}
+          <span className="font-semibold text-slate-900">
+            {formatBigintTokens(BigInt(offer.wage_rate))} per {offer.wage_period.toLowerCase()}
+          </span>
+          . You are not on the payroll until you accept.
+        </p>
+        {offer.expires_at > 0 && (
+          <p className="text-sm text-slate-500">
+            {expired ? "Expired on" : "Expires on"} {new Date(offer.expires_at * 1000).toLocaleString()}
+          </p>
+        )}
+        {errorMsg && <p className="text-sm text-red-600">{errorMsg}</p>}
+        <Button
+          onClick={handleAccept}
+          disabled={isLoading || expired}
+          className="w-full bg-amber-600 hover:bg-amber-700 text-white"
+        >
+          {isLoading ? "Accepting..." : "Accept Offer"}
+        </Button>
+      </CardContent>
+    </Card>
+  );
+};
+
+export default OfferCard;
@@ -10,6 +10,7 @@ import { Badge } from "@/components/ui/badge";
 import { useWallet } from "@/contexts/WalletContext";
 import {
   addEmployee,
+  getOffer,
   // setEmployeeSalary, // (unused)
   payEmployee,
   removeEmployee,
@@ -39,6 +40,7 @@ interface Employee {
   wagePeriod: "hour" | "day" | "week" | "month";
   active: boolean; // on-chain active status
   accruedWages: number;
+  offerPending?: boolean; // offer sent, employee has not accepted yet
 }
 
 const EmployeeManagementCard: React.FC = () => {
@@ -229,7 +231,27 @@ const EmployeeManagementCard: React.FC = () => {
       );
 
       const valid = syncedEmployees.filter((e): e is Employee => Boolean(e));
-      setEmployees(valid);
+
+      // Offers not yet accepted are not on-chain employees; keep them while the offer is open
+      const pendingCandidates = [...employees, ...savedEmployees].filter(
+        (e, i, all) =>
+          e.offerPending &&
+          !employeeAddresses.includes(e.address) &&
+          all.findIndex((other) => other.address === e.address) === i
+      );
+      const stillPending = (
+        await Promise.all(
+          pendingCandidates.map(async (e) => {
+            try {
+              return (await getOffer(fairWageContractId, e.address)) ? e : null;
+            } catch {
+              return e;
+            }
+          })
+        )
+      ).filter((e): e is Employee => Boolean(e));
+
+      setEmployees([...valid, ...stillPending]);
       console.log("✅ Employees synced from blockchain:", valid);
     } catch (error) {
       console.error("❌ Failed to sync employees from contract:", error);
@@ -290,15 +312,16 @@ const EmployeeManagementCard: React.FC = () => {
         name: newEmployee.name,
         wageRate: newEmployee.wageRate,
         wagePeriod: newEmployee.wagePeriod,
-        active: true,
+        active: false,
         accruedWages: 0,
+        offerPending: true,
       };
 
       setEmployees((prev) => [...prev, employee]);
       setNewEmployee({ address: "", name: "", wageRate: 0, wagePeriod: "hour" });
       setIsAddingEmployee(false);
       alert(
-        `Employee ${employee.name} added successfully!\n\nNote: Employee must create trustline for this token to receive payments.`
+        `Offer sent to ${employee.name}!\n\nThey are hired once they accept it from the Employee page. Note: Employee must create trustline for this token to receive payments.`
       );
     } catch (error: any) {
       console.error("❌ Failed to add employee:", error);
@@ -333,10 +356,10 @@ const EmployeeManagementCard: React.FC = () => {
 
         setEmployees((prev) =>
           prev.map((emp) =>
-            emp.id === employeeId ? { ...emp } : emp
+            emp.id === employeeId ? { ...emp, active: false, accruedWages: 0, offerPending: true } : emp
           )
         );
-        alert(`Employee ${employee.name} reset successfully!`);
+        alert(`Employee ${employee.name} reset successfully! They must accept the new offer from the Employee page.`);
       } catch (error: any) {
         console.error("Reset failed:", error);
         alert(`Reset failed: ${error.message}`);
@@ -991,7 +1014,7 @@ const EmployeeManagementCard: React.FC = () => {
                           size="sm"
                           variant="outline"
                           className="border-2 border-blue-400 text-blue-400 hover:bg-blue-500 hover:text-white hover:border-blue-500 transition-all duration-300 shadow-md hover:shadow-blue-400/20 backdrop-blur-sm"
-                          disabled={isUpdatingWage}
+                          disabled={isUpdatingWage || employee.offerPending}
                         >
                           <Edit className="w-4 h-4 mr-1" />
                           Edit Rate
@@ -1010,7 +1033,7 @@ const EmployeeManagementCard: React.FC = () => {
                             Pause
                           </Button>
                         )}
-                        {!employee.active && (
+                        {!employee.active && !employee.offerPending && (
                           <>
                             <Button
                               onClick={() => void handleActivateEmployee(employee.id)}
@@ -1096,8 +1119,11 @@ const EmployeeManagementCard: React.FC = () => {
                     </div>
                     <div>
                       <p className="text-slate-600 font-medium">Status</p>
-                      <Badge variant={employee.active ? "default" : "destructive"} className="shadow-sm">
-                        {employee.active ? "🟢 Active" : "🔴 Frozen"}
+                      <Badge
+                        variant={employee.active ? "default" : employee.offerPending ? "secondary" : "destructive"}
+                        className="shadow-sm"
+                      >
+                        {employee.active ? "🟢 Active" : employee.offerPending ? "🟡 Offer pending" : "🔴 Frozen"}
                       </Badge>
                     </div>
                   </div>
@@ -548,6 +548,7 @@ export const setContractId = (contractId: string): void => {
 };
 
 // Employee management functions (simplified)
+// Sends an employment offer; the employee is only hired once they call acceptOffer
 export const addEmployee = async (
     fairWageContractId: string,
     employeeAddress: string,
@@ -596,6 +597,72 @@ export const addEmployee = async (
     return submitResult.transactionHash;
 };
 
+export interface EmploymentOffer {
+    wage_rate: string;
+    wage_period: string;
+    offered_at: number;
+    expires_at: number;
+}
+
+// Pending offer for an employee, or null if there is none
+export const getOffer = async (
+    fairWageContractId: string,
+    employeeAddress: string
+): Promise<EmploymentOffer | null> => {
+    const response = await fetch(`${process.env.NEXT_PUBLIC_API_URL || 'http://localhost:3001'}/api/get-offer`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ fairWageContractId, employeeAddress })
+    });
+
+    if (!response.ok) {
+        const errorData = await response.json();
+        throw new Error(`Failed to get offer: ${errorData.error}`);
+    }
+
+    const result = await response.json();
+    return result.offer || null;
+};
+
+// Employee accepts the pending offer; signed by the employee's own wallet
+export const acceptOffer = async (fairWageContractId: string): Promise<string> => {
+    if (!window.rabet) throw new Error("Rabet wallet not found.");
+    const { publicKey } = await window.rabet.connect();
+
+    const response = await fetch(`${process.env.NEXT_PUBLIC_API_URL || 'http://localhost:3001'}/api/accept-offer`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({
+            userPublicKey: publicKey,
+            fairWageContractId
+        })
+    });
+
+    if (!response.ok) {
+        const errorData = await response.json();
+        throw new Error(`Failed to accept offer: ${errorData.error}`);
+    }
+
+    const result = await response.json();
+
+    const signResult = await window.rabet.sign(result.transactionXdr, 'TESTNET');
+    if (!signResult.xdr) throw new Error("Signing cancelled");
+
+    const submitResponse = await fetch(`${process.env.NEXT_PUBLIC_API_URL || 'http://localhost:3001'}/api/submit-transaction`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ signedTransactionXdr: signResult.xdr })
+    });
+
+    if (!submitResponse.ok) {
+        const errorData = await submitResponse.json();
+        throw new Error(`Submit failed: ${errorData.error}`);
+    }
+
+    const submitResult = await submitResponse.json();
+    return submitResult.transactionHash;
+};
+
 // Pay all wages function
 export const payAllWages = async (
     fairWageContractId: string,
@@ -1130,6 +1197,8 @@ export default {
     getCurrentContractId,
     setContractId,
     addEmployee,
+    getOffer,
+    acceptOffer,
     payAllWages,
     payAllEmployees,
     getEmployeeBalance,